@@ -0,0 +1,147 @@
+use serde::{Deserialize, Serialize};
+use tetris::{moves::Move, state::State};
+
+use crate::{
+    bot::BotError,
+    eval::{Evaluator, Term, Weights},
+    node::Node,
+    preset::Preset,
+};
+
+// Weights that shift with the position: attack while the stack is low, defense as it climbs or
+// garbage piles up, and combo weights while a combo is running
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Blend {
+    pub attack: Weights,
+    pub defense: Weights,
+    pub combo: Weights,
+    // Stack height where defense starts to take over, and where it has taken over fully
+    pub low: u32,
+    pub high: u32,
+    // Extra room before defending while back to back is up, so the chain isn't given up too early
+    pub b2b_room: u32,
+    // Combo at which the combo weights have taken over fully
+    pub combo_full: u8,
+}
+
+impl Default for Blend {
+    fn default() -> Self {
+        Self {
+            attack: Preset::Aggressive.weights(),
+            defense: Preset::Defensive.weights(),
+            combo: Preset::Combo.weights(),
+            low: 6,
+            high: 14,
+            b2b_room: 2,
+            combo_full: 4,
+        }
+    }
+}
+
+impl Blend {
+    pub fn from_json(json: &str) -> Result<Self, BotError> {
+        serde_json::from_str(json).map_err(|error| BotError::InvalidWeights(error.to_string()))
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("weights always serialize")
+    }
+
+    // How far towards defense, each line of pending garbage counts as a row of stack
+    pub fn danger(&self, state: &State, pending: u16) -> f32 {
+        let height = state.board.heights().into_iter().max().unwrap() + pending as u32;
+        let low = match state.b2b > 0 {
+            true => self.low + self.b2b_room,
+            false => self.low,
+        };
+        let high = self.high.max(low + 1);
+
+        (height.saturating_sub(low) as f32 / (high - low) as f32).min(1.0)
+    }
+
+    pub fn weights(&self, state: &State, pending: u16) -> Weights {
+        let danger = self.danger(state, pending);
+        let combo = (state.combo.saturating_sub(1) as f32 / self.combo_full.max(1) as f32).min(1.0);
+
+        let mut weights = match danger > 0.0 {
+            true => self.attack.lerp(&self.defense, danger),
+            false => self.attack,
+        };
+
+        if combo > 0.0 {
+            weights = weights.lerp(&self.combo, combo);
+        }
+
+        weights
+    }
+}
+
+impl Evaluator for Blend {
+    fn evaluate(&self, node: &mut Node, mv: Move) {
+        self.weights(&node.state, node.pending).evaluate(node, mv);
+    }
+
+    fn explain(&self, node: &mut Node, mv: Move) -> Vec<Term> {
+        self.weights(&node.state, node.pending).explain(node, mv)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn weights() {
+        let blend = Blend::default();
+        let mut state = State::new();
+
+        assert_eq!(blend.weights(&state, 0), blend.attack);
+
+        // Pending garbage counts towards the height until defense has taken over
+        state.board.add_garbage(10, 0);
+
+        assert_eq!(blend.weights(&state, 4), blend.defense);
+        assert_ne!(blend.weights(&state, 0), blend.attack);
+        assert_ne!(blend.weights(&state, 0), blend.defense);
+
+        // Back to back buys some room before defending
+        let before = blend.danger(&state, 0);
+
+        state.b2b = 1;
+
+        assert!(blend.danger(&state, 0) < before);
+
+        // A running combo leans on the combo weights whatever the height
+        state.combo = 1 + blend.combo_full;
+
+        assert_eq!(blend.weights(&state, 0), blend.combo);
+    }
+
+    #[test]
+    fn lerp() {
+        let attack = Preset::Aggressive.weights();
+        let defense = Preset::Defensive.weights();
+        let half = attack.lerp(&defense, 0.5);
+
+        assert_eq!(attack.lerp(&defense, 0.0), attack);
+        assert_eq!(attack.lerp(&defense, 1.0), defense);
+        assert_eq!(half.height, (attack.height + defense.height) / 2);
+
+        // Every weight moves, not only those the presets disagree on
+        let mut zero = Weights::default();
+        let mut count = Weights::default();
+
+        for (i, (zero, count)) in zero
+            .params_mut()
+            .into_iter()
+            .zip(count.params_mut())
+            .enumerate()
+        {
+            *zero = 0;
+            *count = i as i32 + 1;
+        }
+
+        assert_eq!(zero.lerp(&count, 1.0), count);
+    }
+}
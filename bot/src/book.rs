@@ -0,0 +1,171 @@
+use serde::{Deserialize, Serialize};
+
+use tetris::{board::Board, movegen::movegen, moves::Move, piece::Piece, state::State};
+
+use crate::bot::BotError;
+
+// A single opening line: the bag prefix it is built for and the placements to play in order
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Opener {
+    pub name: String,
+    pub queue: Vec<Piece>,
+    pub moves: Vec<Move>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Entry {
+    board: Board,
+    hold: Option<Piece>,
+    next: usize,
+    mv: Move,
+    opener: usize,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Book {
+    openers: Vec<Opener>,
+    entries: Vec<Entry>,
+}
+
+impl Book {
+    pub fn new(openers: Vec<Opener>) -> Result<Self, BotError> {
+        let mut entries = Vec::new();
+
+        for (index, opener) in openers.iter().enumerate() {
+            let mut state = State::new();
+
+            for mv in &opener.moves {
+                let current = opener.queue.get(state.next);
+                let hold = state
+                    .hold
+                    .or_else(|| opener.queue.get(state.next + 1).copied());
+
+                if current != Some(&mv.kind) && hold != Some(mv.kind) {
+                    return Err(BotError::InvalidBook(opener.name.clone()));
+                }
+
+                if !movegen(&state.board, mv.kind).contains(mv) {
+                    return Err(BotError::InvalidBook(opener.name.clone()));
+                }
+
+                entries.push(Entry {
+                    board: state.board,
+                    hold: state.hold,
+                    next: state.next,
+                    mv: *mv,
+                    opener: index,
+                });
+
+                state.make(mv, &opener.queue);
+            }
+        }
+
+        Ok(Self { openers, entries })
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, BotError> {
+        let openers =
+            serde_json::from_str(json).map_err(|error| BotError::InvalidBook(error.to_string()))?;
+
+        Self::new(openers)
+    }
+
+    pub fn openers(&self) -> &[Opener] {
+        &self.openers
+    }
+
+    // Find the book move for this position, the visible queue must agree with the rest of the line
+    pub fn lookup(&self, root: &State, queue: &[Piece]) -> Option<Move> {
+        self.entries
+            .iter()
+            .find(|entry| {
+                let line = &self.openers[entry.opener].queue[entry.next..];
+
+                entry.board == root.board
+                    && entry.hold == root.hold
+                    && line.iter().zip(queue.iter()).all(|(a, b)| a == b)
+            })
+            .map(|entry| entry.mv)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tetris::{piece::Rotation, state::Lock};
+
+    use super::*;
+    use crate::{
+        bot::{BotConfigs, BotState},
+        eval::Weights,
+    };
+
+    const CONFIGS: BotConfigs = BotConfigs {
+        width: 16,
+        depth: 3,
+        branch: 0,
+        threads: 1,
+    };
+
+    fn opener() -> Opener {
+        Opener {
+            name: String::from("test"),
+            queue: vec![Piece::I, Piece::O, Piece::L],
+            moves: vec![
+                Move {
+                    x: 1,
+                    y: 0,
+                    r: Rotation::North,
+                    kind: Piece::I,
+                    tspin: None,
+                },
+                Move {
+                    x: 8,
+                    y: 0,
+                    r: Rotation::North,
+                    kind: Piece::O,
+                    tspin: None,
+                },
+            ],
+        }
+    }
+
+    fn bot(queue: Vec<Piece>) -> BotState {
+        let lock = Lock::default();
+        let mut bot = BotState::new(State::new(), lock, queue, Weights::default()).unwrap();
+
+        bot.set_book(Book::new(vec![opener()]).unwrap());
+        bot
+    }
+
+    #[test]
+    fn invalid() {
+        let mut opener = opener();
+
+        opener.moves[1].kind = Piece::T;
+
+        assert!(Book::new(vec![opener]).is_err());
+    }
+
+    #[test]
+    fn follow() {
+        let mut bot = bot(vec![Piece::I, Piece::O, Piece::L, Piece::J]);
+
+        for (mv, next) in opener().moves.into_iter().zip([Piece::S, Piece::Z]) {
+            let result = bot.search(CONFIGS).unwrap();
+
+            assert_eq!(result.candidates.len(), 1);
+            assert_eq!(result.candidates[0].mv, mv);
+
+            bot.make(mv, &[next]).unwrap();
+        }
+
+        assert!(bot.search(CONFIGS).unwrap().candidates.len() > 1);
+    }
+
+    #[test]
+    fn mismatch() {
+        let mut bot = bot(vec![Piece::I, Piece::L, Piece::O, Piece::J]);
+
+        assert!(bot.search(CONFIGS).unwrap().candidates.len() > 1);
+    }
+}
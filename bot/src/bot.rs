@@ -1,4 +1,10 @@
-use std::cmp::Ordering;
+use std::{
+    cmp::Ordering,
+    collections::HashMap,
+    sync::atomic::{AtomicUsize, Ordering as AtomicOrdering},
+    thread,
+    time::Instant,
+};
 use thiserror::Error;
 
 use tetris::{
@@ -11,8 +17,10 @@ use tetris::{
 };
 
 use crate::{
-    eval::{Weights, evaluate},
-    node::Node,
+    book::Book,
+    eval::{Evaluator, Term, Weights},
+    garbage::{Garbage, dig, receive, total},
+    node::{Node, Step},
     selector::Selector,
 };
 
@@ -22,14 +30,26 @@ pub enum BotError {
     InvalidQueue,
     #[error("bot dead")]
     Death,
+    #[error("invalid book: {0}")]
+    InvalidBook(String),
+    #[error("invalid weights: {0}")]
+    InvalidWeights(String),
+    #[error("invalid model: {0}")]
+    InvalidModel(String),
 }
 
 #[derive(Debug, Clone)]
-pub struct BotState {
+pub struct BotState<E = Weights> {
     root: State,
     lock: Lock,
     queue: Vec<Piece>,
-    weights: Weights,
+    evaluator: E,
+    book: Option<Book>,
+    incoming: Vec<Garbage>,
+    // Garbage rows at the bottom of the root board, they leave as they are cleared
+    garbage: u8,
+    // Beam layers left from the last search, the first one holds children of the root
+    tree: Vec<Vec<Node>>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -40,27 +60,64 @@ pub struct BotScore {
 
 #[derive(Debug, Clone, Copy)]
 pub struct BotConfigs {
+    // Nodes kept per depth
     pub width: usize,
+    // Placements searched ahead, past the known queue the rest of the bag is speculated
     pub depth: usize,
+    // Children kept per parent, 0 keeps them all
     pub branch: usize,
+    // Threads sharing each depth, 0 and 1 both search on the calling thread
+    pub threads: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Limit {
+    Deadline(Instant),
+    Nodes(usize),
+}
+
+// With adaptive set, the beam width is rescaled after every depth so the search fits the limit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Budget {
+    pub limit: Limit,
+    pub adaptive: bool,
+}
+
+// A root move with its score, the best line found under it starting with the move itself, and
+// how the evaluator scored the move on its own, steps of the line past the queue only hold for the
+// piece they guessed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Candidate {
+    pub mv: Move,
+    pub score: BotScore,
+    pub line: Vec<Step>,
+    pub terms: Vec<Term>,
 }
 
 #[derive(Debug, Clone)]
 pub struct BotResult {
-    pub candidates: Vec<(Move, BotScore)>,
+    pub candidates: Vec<Candidate>,
     pub nodes: usize,
     pub depth: usize,
-    root: State,
-    queue: Vec<Piece>,
 }
 
-impl BotState {
-    pub fn new(
-        root: State,
-        lock: Lock,
-        queue: Vec<Piece>,
-        weights: Weights,
-    ) -> Result<Self, BotError> {
+struct Context<'a, E> {
+    queue: &'a [Piece],
+    incoming: &'a [Garbage],
+    evaluator: &'a E,
+    limit: Option<Limit>,
+    branch: usize,
+    threads: usize,
+}
+
+// How far the adaptive width may move away from the configured one, in both directions
+const ADAPTIVE_RANGE: usize = 8;
+
+// Score of a speculated piece we can't place at all
+const DEATH: i64 = -1_000_000;
+
+impl<E: Evaluator> BotState<E> {
+    pub fn new(root: State, lock: Lock, queue: Vec<Piece>, evaluator: E) -> Result<Self, BotError> {
         if queue.len() < 2 || !is_queue_valid(&queue, root.bag) {
             return Err(BotError::InvalidQueue);
         }
@@ -69,7 +126,11 @@ impl BotState {
             root,
             lock,
             queue,
-            weights,
+            evaluator,
+            book: None,
+            incoming: Vec::new(),
+            garbage: 0,
+            tree: Vec::new(),
         })
     }
 
@@ -81,7 +142,43 @@ impl BotState {
         &self.queue
     }
 
-    pub fn make(&mut self, mv: Move, new_pieces: &[Piece]) -> Result<(), BotError> {
+    pub fn garbage(&self) -> u8 {
+        self.garbage
+    }
+
+    // Scores depend on how much garbage is left to dig, so the kept tree goes
+    pub fn set_garbage(&mut self, rows: u8) {
+        if self.garbage != rows {
+            self.tree.clear();
+        }
+
+        self.garbage = rows;
+    }
+
+    pub fn evaluator(&self) -> &E {
+        &self.evaluator
+    }
+
+    // The kept tree was scored by the old evaluator, so it goes with it
+    pub fn set_evaluator(&mut self, evaluator: E) {
+        self.evaluator = evaluator;
+        self.tree.clear();
+    }
+
+    pub fn set_book(&mut self, book: Book) {
+        self.book = Some(book);
+    }
+
+    // Garbage queued against us, in the order it will land
+    pub fn set_incoming(&mut self, incoming: &[Garbage]) {
+        if self.incoming != incoming {
+            self.tree.clear();
+        }
+
+        self.incoming = incoming.to_vec();
+    }
+
+    pub fn make(&mut self, mv: Move, new_pieces: &[Piece]) -> Result<Lock, BotError> {
         let mut bag = self.root.bag;
         for kind in &self.queue {
             update_bag(&mut bag, *kind);
@@ -91,12 +188,61 @@ impl BotState {
             return Err(BotError::InvalidQueue);
         }
 
+        let known = self.queue.len();
+
+        self.garbage = dig(self.garbage, &self.root.board, &mv);
         self.lock = self.root.make(&mv, &self.queue);
+        self.prune(known);
         self.queue.extend(new_pieces);
         self.queue.drain(..self.root.next);
         self.root.next = 0;
 
-        Ok(())
+        Ok(self.lock)
+    }
+
+    // Keep what grew under the move just played, dropping everything that guessed unseen pieces
+    fn prune(&mut self, known: usize) {
+        let mut layers = std::mem::take(&mut self.tree).into_iter();
+
+        // Garbage landing under the tree would make its boards stale
+        if !self.incoming.is_empty() {
+            return;
+        }
+
+        let Some(first) = layers.next() else {
+            return;
+        };
+        let Some(played) = first.iter().find(|node| node.state == self.root) else {
+            return;
+        };
+
+        let consumed = self.root.next;
+        let offset = played.reward;
+        let cleared = played.lines;
+        let mut map: Vec<_> = first
+            .iter()
+            .map(|node| (node.state == self.root).then_some(0))
+            .collect();
+
+        for layer in layers {
+            let (pruned, next) = descend(layer, &map, |node| {
+                if node.state.next > known {
+                    return false;
+                }
+
+                node.state.next -= consumed;
+                node.reward -= offset;
+                node.lines -= cleared;
+                true
+            });
+
+            if pruned.is_empty() {
+                break;
+            }
+
+            self.tree.push(pruned);
+            map = next;
+        }
     }
 
     pub fn reset(&mut self, board: Board, b2b: u8, combo: u8) -> Result<(), BotError> {
@@ -104,43 +250,90 @@ impl BotState {
         self.root.b2b = b2b;
         self.root.combo = combo;
         self.root.next = 0;
+        self.tree.clear();
 
         Ok(())
     }
 
-    pub fn search(&self, configs: BotConfigs) -> Result<BotResult, BotError> {
+    pub fn search(&mut self, configs: BotConfigs) -> Result<BotResult, BotError> {
+        self.run(configs, None)
+    }
+
+    // Deepen until the budget runs out, returning what the last finished depth found
+    pub fn search_within(
+        &mut self,
+        configs: BotConfigs,
+        budget: Budget,
+    ) -> Result<BotResult, BotError> {
+        self.run(configs, Some(budget))
+    }
+
+    fn run(&mut self, configs: BotConfigs, budget: Option<Budget>) -> Result<BotResult, BotError> {
         let mut result = BotResult {
             candidates: Vec::new(),
             nodes: 0,
             depth: 0,
-            root: self.root.clone(),
-            queue: self.queue.clone(),
         };
-        let mut parents = Vec::with_capacity(configs.width);
-        let mut children = Selector::new(configs.width);
+
+        // Follow the opener book while the position is still on a known line
+        if let Some(mv) = self
+            .book
+            .as_ref()
+            .and_then(|book| book.lookup(&self.root, &self.queue))
+        {
+            result.candidates.push(Candidate {
+                mv,
+                score: BotScore { depth: 0, score: 0 },
+                line: vec![Step {
+                    mv,
+                    hold: mv.kind != self.queue[0],
+                    chance: None,
+                }],
+                terms: Vec::new(),
+            });
+            self.tree.clear();
+
+            return Ok(result);
+        }
+
+        let context = Context {
+            queue: &self.queue,
+            incoming: &self.incoming,
+            evaluator: &self.evaluator,
+            limit: budget.map(|b| b.limit),
+            branch: configs.branch,
+            threads: configs.threads,
+        };
+        let mut width = configs.width;
+        let mut parents = Vec::with_capacity(width);
+        let mut children = Selector::new(width);
         let root = Node {
-            state: State {
-                next: 0,
-                ..self.root
-            },
-            lock: self.lock,
-            value: 0,
-            reward: 0,
-            index: 0,
+            pending: total(&self.incoming),
+            garbage: self.garbage,
+            ..Node::new(
+                State {
+                    next: 0,
+                    ..self.root
+                },
+                self.lock,
+            )
         };
 
-        result.nodes = expand(&root, &self.queue, |mut child, mv| {
+        result.nodes = expand(&context, &root, 1, |mut child, mv| {
             child.index = result.candidates.len();
+            child.parent = 0;
 
-            evaluate(&mut child, mv, &self.weights);
+            let terms = self.evaluator.explain(&mut child, mv);
 
-            result.candidates.push((
+            result.candidates.push(Candidate {
                 mv,
-                BotScore {
+                score: BotScore {
                     depth: 0,
                     score: child.reward as i64 + child.value as i64,
                 },
-            ));
+                line: Vec::new(),
+                terms,
+            });
 
             parents.push(child);
         });
@@ -151,99 +344,604 @@ impl BotState {
             return Err(BotError::Death);
         }
 
-        result.depth = 1;
-        while result.depth < self.queue.len() - self.root.hold.is_none() as usize {
-            result.nodes += think(
-                &mut parents,
+        let max_depth = configs.depth;
+        let mut layers = vec![parents];
+        let mut values = graft(
+            &self.queue,
+            &mut layers,
+            std::mem::take(&mut self.tree),
+            &mut result.candidates,
+            max_depth,
+        );
+
+        result.depth = layers.len();
+        while result.depth < max_depth {
+            if context.is_stopped(result.nodes) {
+                break;
+            }
+
+            let start = Instant::now();
+            let parents = layers.last().expect("the root children are always there");
+            let mut scores = vec![None; parents.len()];
+            let mut beam = Vec::with_capacity(width);
+            let (nodes, finished) = think(
+                &context,
+                parents,
+                &mut beam,
                 &mut children,
-                &self.queue,
+                &mut scores,
+                result.depth,
+                result.nodes,
+            );
+
+            result.nodes += nodes;
+
+            // Only a fully searched depth is trusted
+            if !finished {
+                break;
+            }
+
+            values = backup(
+                context.queue,
+                &layers,
+                scores,
                 &mut result.candidates,
-                &self.weights,
                 result.depth,
             );
             result.depth += 1;
+            layers.push(beam);
+
+            if let Some(budget) = budget.filter(|b| b.adaptive) {
+                let left = max_depth.saturating_sub(result.depth).max(1);
+                let fit = budget.limit.fit(start, nodes, result.nodes, left);
+
+                width = ((width as f64 * fit) as usize).clamp(
+                    (configs.width / ADAPTIVE_RANGE).max(1),
+                    configs.width * ADAPTIVE_RANGE,
+                );
+                children.set_size(width);
+            }
+        }
+
+        for (index, line) in lines(&layers, &values).into_iter().enumerate() {
+            result.candidates[index].line = line;
         }
 
+        self.tree = layers;
+
         Ok(result)
     }
 }
 
-pub fn best_move(result: &BotResult, incomming: i32) -> Result<Move, BotError> {
+impl Limit {
+    pub fn is_reached(&self, nodes: usize) -> bool {
+        match *self {
+            Self::Deadline(deadline) => Instant::now() >= deadline,
+            Self::Nodes(budget) => nodes >= budget,
+        }
+    }
+
+    // How much the last depth's cost must scale so the remaining depths share what is left
+    fn fit(&self, start: Instant, nodes: usize, total: usize, depths: usize) -> f64 {
+        let (spent, left) = match *self {
+            Self::Deadline(deadline) => {
+                let now = Instant::now();
+
+                (
+                    now.duration_since(start).as_secs_f64(),
+                    deadline.saturating_duration_since(now).as_secs_f64(),
+                )
+            }
+            Self::Nodes(budget) => (nodes as f64, budget.saturating_sub(total) as f64),
+        };
+
+        match spent > 0.0 {
+            true => left / depths as f64 / spent,
+            false => ADAPTIVE_RANGE as f64,
+        }
+    }
+}
+
+impl<E> Context<'_, E> {
+    fn is_stopped(&self, nodes: usize) -> bool {
+        self.limit.is_some_and(|limit| limit.is_reached(nodes))
+    }
+}
+
+pub fn best_move(result: &BotResult) -> Result<Move, BotError> {
     let best = result
         .candidates
         .iter()
-        .filter(|candidate| {
-            let mut root = result.root.clone();
-            let lock = root.make(&candidate.0, &result.queue);
-            let heights = *root.board.heights()[3..7].iter().max().unwrap();
-
-            heights as i32 + incomming - lock.sent as i32 <= 20
-        })
-        .max_by_key(|c| c.1)
+        .max_by_key(|c| c.score)
         .ok_or(BotError::Death)?;
 
-    Ok(best.0)
+    Ok(best.mv)
 }
 
-fn expand(node: &Node, queue: &[Piece], mut callback: impl FnMut(Node, Move)) -> usize {
+// Pieces the next unknown piece may be, empty while the queue still covers this node
+fn chances(queue: &[Piece], state: &State) -> Bag {
+    match queue.get(state.next) {
+        Some(&current) if state.hold.is_none() && state.next + 1 >= queue.len() => {
+            let mut bag = state.bag;
+
+            update_bag(&mut bag, current);
+            bag
+        }
+        Some(_) => Bag::empty(),
+        None => state.bag,
+    }
+}
+
+// Play a move where the pieces from `state.next` on are given by `pieces`
+fn make(state: &mut State, mv: &Move, pieces: &[Piece]) -> Lock {
+    let next = state.next;
+
+    state.next = 0;
+
+    let lock = state.make(mv, pieces);
+
+    state.next += next;
+    lock
+}
+
+// Generate every child, past the queue each child's step is tagged with the speculated piece it
+// relies on
+fn expand<E>(
+    context: &Context<E>,
+    node: &Node,
+    depth: usize,
+    mut callback: impl FnMut(Node, Move),
+) -> usize {
+    let queue = context.queue;
+    let next = node.state.next;
+    let chances = chances(queue, &node.state);
+
+    let mut cache: [Option<Vec<Move>>; 7] = Default::default();
     let mut nodes = 0;
 
-    let current = queue[node.state.next];
-    let hold = node
-        .state
-        .hold
-        .unwrap_or_else(|| queue[node.state.next + 1]);
+    let mut play = |pieces: &[Piece], kind: Piece, hold: bool, chance: Option<Piece>| {
+        let moves = cache[kind as usize].get_or_insert_with(|| movegen(&node.state.board, kind));
+
+        for mv in moves.iter() {
+            let mut child = node.clone();
+            child.garbage = dig(node.garbage, &node.state.board, mv);
+            child.lock = make(&mut child.state, mv, pieces);
+            child.lines = node.lines + child.lock.cleared as u16;
+
+            // Holding into a guess of the same kind looks like no hold at all to `State::make`
+            if hold && child.state.hold.is_none() {
+                child.state.hold = Some(kind);
+                child.state.next += 1;
+                update_bag(&mut child.state.bag, kind);
+            }
+            child.step = Some(Step {
+                mv: *mv,
+                hold,
+                chance,
+            });
+
+            if receive(&mut child, context.incoming, depth) {
+                callback(child, *mv);
+            }
+        }
 
-    for kind in [current, hold] {
-        let moves = movegen(&node.state.board, kind);
         nodes += moves.len();
+    };
 
-        for mv in moves {
-            let mut child = node.clone();
-            child.lock = child.state.make(&mv, queue);
-            callback(child, mv);
+    match queue.get(next) {
+        Some(&current) => {
+            play(&queue[next..], current, false, None);
+
+            match node.state.hold.or_else(|| queue.get(next + 1).copied()) {
+                Some(hold) if hold != current => play(&queue[next..], hold, true, None),
+                _ => {}
+            }
+
+            // Holding into a piece we can't see yet
+            for piece in chances {
+                play(&[current, piece], piece, true, Some(piece));
+            }
         }
+        None => {
+            for piece in chances {
+                play(&[piece], piece, false, Some(piece));
 
-        if current == hold {
-            break;
+                if let Some(hold) = node.state.hold
+                    && hold != piece
+                {
+                    play(&[piece], hold, true, Some(piece));
+                }
+            }
         }
     }
 
     nodes
 }
 
-fn think(
-    beam: &mut Vec<Node>,
-    selector: &mut Selector,
+fn chance(node: &Node) -> Option<Piece> {
+    node.step.and_then(|step| step.chance)
+}
+
+// Average over the unknown piece of the best child we could pick once it is revealed, children
+// that didn't guess it fit every piece and `missing` stands in for a piece with none
+fn expectation(
+    children: impl Iterator<Item = (Option<Piece>, i64)>,
+    chances: Bag,
+    missing: i64,
+) -> i64 {
+    let mut best = [None; 7];
+    let mut any = None;
+
+    for (chance, score) in children {
+        let slot = match chance {
+            Some(piece) => &mut best[piece as usize],
+            None => &mut any,
+        };
+
+        *slot = (*slot).max(Some(score));
+    }
+
+    let sum: i64 = chances
+        .into_iter()
+        .map(|piece| best[piece as usize].max(any).unwrap_or(missing))
+        .sum();
+
+    sum / chances.len() as i64
+}
+
+// Carry the scores of the last layer's nodes up to the root children and credit their candidates
+// at this depth, taking the best child where the move is ours to pick and the expectation where
+// an unseen piece decides, returns the score backed up to every node of every layer
+fn backup(
     queue: &[Piece],
-    candidates: &mut Vec<(Move, BotScore)>,
-    weights: &Weights,
+    layers: &[Vec<Node>],
+    mut scores: Vec<Option<i64>>,
+    candidates: &mut [Candidate],
     depth: usize,
-) -> usize {
+) -> Vec<Vec<Option<i64>>> {
+    let mut values = Vec::with_capacity(layers.len());
+
+    for parents in (0..layers.len() - 1).rev() {
+        let children = &layers[parents + 1];
+        let mut grouped = vec![Vec::new(); layers[parents].len()];
+
+        // A piece whose every child was cut from the beam did no better than the worst one kept,
+        // the layers are sorted worst first
+        let floor = children
+            .first()
+            .map_or(DEATH, |child| child.value as i64 + child.reward as i64);
+
+        for (child, score) in children.iter().zip(&scores) {
+            if let Some(score) = *score {
+                grouped[child.parent].push((chance(child), score));
+            }
+        }
+
+        let backed = layers[parents]
+            .iter()
+            .zip(grouped)
+            .map(|(node, children)| {
+                let chances = chances(queue, &node.state);
+
+                match (children.is_empty(), chances.is_empty()) {
+                    (true, _) => None,
+                    (false, true) => children.into_iter().map(|(_, score)| score).max(),
+                    (false, false) => Some(expectation(children.into_iter(), chances, floor)),
+                }
+            })
+            .collect();
+
+        values.push(std::mem::replace(&mut scores, backed));
+    }
+
+    for (node, score) in layers[0].iter().zip(&scores) {
+        if let Some(score) = *score {
+            candidates[node.index].score = BotScore { depth, score };
+        }
+    }
+
+    values.push(scores);
+    values.reverse();
+
+    values
+}
+
+// Expand the beam by one depth, returning the nodes generated and whether the depth was finished,
+// each parent's score from its children goes in `scores`
+fn think<E: Evaluator>(
+    context: &Context<E>,
+    beam: &[Node],
+    next: &mut Vec<Node>,
+    selector: &mut Selector,
+    scores: &mut [Option<i64>],
+    depth: usize,
+    searched: usize,
+) -> (usize, bool) {
+    let counter = AtomicUsize::new(searched);
+    let threads = context.threads.clamp(1, beam.len().max(1));
+
+    let (nodes, finished) = match threads {
+        1 => think_slice(context, beam, 0, selector, scores, depth, &counter),
+        _ => {
+            let chunk = beam.len().div_ceil(threads);
+            let size = selector.size();
+
+            // Every slice gets its own selector, merged back in slice order
+            let slices = thread::scope(|scope| {
+                let handles: Vec<_> = beam
+                    .chunks(chunk)
+                    .zip(scores.chunks_mut(chunk))
+                    .enumerate()
+                    .map(|(i, (slice, scores))| {
+                        let counter = &counter;
+
+                        scope.spawn(move || {
+                            let mut local = Selector::new(size);
+                            let (nodes, finished) = think_slice(
+                                context,
+                                slice,
+                                i * chunk,
+                                &mut local,
+                                scores,
+                                depth,
+                                counter,
+                            );
+
+                            (local, nodes, finished)
+                        })
+                    })
+                    .collect();
+
+                handles
+                    .into_iter()
+                    .map(|handle| handle.join().expect("search thread panicked"))
+                    .collect::<Vec<_>>()
+            });
+
+            let mut nodes = 0;
+            let mut finished = true;
+
+            for (mut local, slice_nodes, slice_finished) in slices {
+                nodes += slice_nodes;
+                finished &= slice_finished;
+
+                while let Some(child) = local.pop_worst() {
+                    selector.push(child);
+                }
+            }
+
+            (nodes, finished)
+        }
+    };
+
+    if !finished {
+        selector.clear();
+
+        return (nodes, false);
+    }
+
+    while let Some(child) = selector.pop_worst() {
+        next.push(child);
+    }
+    selector.clear();
+
+    (nodes, true)
+}
+
+// Expand some parents into the selector, best parents first, `offset` is where they sit in the beam
+fn think_slice<E: Evaluator>(
+    context: &Context<E>,
+    parents: &[Node],
+    offset: usize,
+    selector: &mut Selector,
+    scores: &mut [Option<i64>],
+    depth: usize,
+    counter: &AtomicUsize,
+) -> (usize, bool) {
     let mut nodes = 0;
+    let mut kept = Vec::new();
+
+    for (i, parent) in parents.iter().enumerate().rev() {
+        if context.is_stopped(counter.load(AtomicOrdering::Relaxed)) {
+            return (nodes, false);
+        }
 
-    while let Some(parent) = beam.pop() {
-        nodes += expand(&parent, &queue, |mut child, mv| {
-            evaluate(&mut child, mv, &weights);
+        let chances = chances(context.queue, &parent.state);
+        let direct = chances.is_empty() && context.branch == 0;
+        let mut best = None;
 
-            let score = BotScore {
-                depth: depth,
-                score: child.reward as i64 + child.value as i64,
-            };
-            if candidates[child.index].1 < score {
-                candidates[child.index].1 = score;
+        let generated = expand(context, parent, depth + 1, |mut child, mv| {
+            child.parent = offset + i;
+
+            context.evaluator.evaluate(&mut child, mv);
+
+            best = best.max(Some(child.reward as i64 + child.value as i64));
+
+            match direct {
+                true => selector.push(child),
+                false => kept.push(child),
             }
+        });
+
+        nodes += generated;
+        counter.fetch_add(generated, AtomicOrdering::Relaxed);
+
+        scores[i] = match chances.is_empty() {
+            true => best,
+            false => Some(expectation(
+                kept.iter()
+                    .map(|child| (chance(child), child.value as i64 + child.reward as i64)),
+                chances,
+                DEATH,
+            )),
+        };
+
+        // Only the parent's best children compete for the next beam, one per speculated piece
+        if !chances.is_empty() {
+            let mut best: [Option<Node>; 7] = Default::default();
+
+            kept.retain_mut(|child| {
+                let Some(piece) = chance(child) else {
+                    return true;
+                };
+
+                let slot = &mut best[piece as usize];
+
+                if slot.as_ref().is_none_or(|best| *best < *child) {
+                    *slot = Some(child.clone());
+                }
+
+                false
+            });
+
+            for child in best.into_iter().flatten() {
+                selector.push(child);
+            }
+        }
+
+        if context.branch > 0 && kept.len() > context.branch {
+            kept.select_nth_unstable_by(context.branch, |a, b| b.cmp(a));
+            kept.truncate(context.branch);
+        }
 
+        for child in kept.drain(..) {
             selector.push(child);
+        }
+    }
+
+    (nodes, true)
+}
+
+// Keep the nodes whose parent survived, `map` gives each old parent position its new one
+fn descend(
+    layer: Vec<Node>,
+    map: &[Option<usize>],
+    mut keep: impl FnMut(&mut Node) -> bool,
+) -> (Vec<Node>, Vec<Option<usize>>) {
+    let mut kept = Vec::with_capacity(layer.len());
+    let mut next = vec![None; layer.len()];
+
+    for (i, mut node) in layer.into_iter().enumerate() {
+        let Some(parent) = map[node.parent] else {
+            continue;
+        };
+
+        node.parent = parent;
+
+        if keep(&mut node) {
+            next[i] = Some(kept.len());
+            kept.push(node);
+        }
+    }
+
+    (kept, next)
+}
+
+// Hang a previous search under the fresh root children it grew from, crediting their candidates,
+// returns the scores backed up through the grafted layers
+fn graft(
+    queue: &[Piece],
+    layers: &mut Vec<Vec<Node>>,
+    tree: Vec<Vec<Node>>,
+    candidates: &mut [Candidate],
+    max_depth: usize,
+) -> Vec<Vec<Option<i64>>> {
+    let mut tree = tree.into_iter();
+    let mut values = Vec::new();
+
+    let Some(first) = tree.next() else {
+        return values;
+    };
+
+    let roots: HashMap<&State, usize> = layers[0]
+        .iter()
+        .enumerate()
+        .map(|(i, node)| (&node.state, i))
+        .collect();
+    let mut map: Vec<_> = first
+        .iter()
+        .map(|node| roots.get(&node.state).copied())
+        .collect();
+
+    for layer in tree {
+        if layers.len() >= max_depth {
+            break;
+        }
+
+        let depth = layers.len();
+        let parents = &layers[depth - 1];
+        let (grafted, next) = descend(layer, &map, |node| {
+            node.index = parents[node.parent].index;
+            true
         });
+
+        if grafted.is_empty() {
+            break;
+        }
+
+        let scores = grafted
+            .iter()
+            .map(|node| Some(node.reward as i64 + node.value as i64))
+            .collect();
+
+        layers.push(grafted);
+        values = backup(queue, layers, scores, candidates, depth);
+        map = next;
     }
 
-    while let Some(child) = selector.pop_worst() {
-        beam.push(child);
+    values
+}
+
+// Walk down from each root move through the child with the best backed up score, the layers
+// past `values` are leaves scored on their own, under a chance node the line follows the best
+// guess and its steps are marked with the piece guessed
+fn lines(layers: &[Vec<Node>], values: &[Vec<Option<i64>>]) -> Vec<Vec<Step>> {
+    let value = |depth: usize, i: usize| match values.get(depth) {
+        Some(scores) => scores[i],
+        None => Some(layers[depth][i].value as i64 + layers[depth][i].reward as i64),
+    };
+
+    // Best child of every node, ties go to the child the beam ranked higher
+    let best: Vec<Vec<Option<(usize, i64)>>> = layers
+        .windows(2)
+        .enumerate()
+        .map(|(depth, pair)| {
+            let mut best = vec![None; pair[0].len()];
+
+            for (i, child) in pair[1].iter().enumerate() {
+                let Some(score) = value(depth + 1, i) else {
+                    continue;
+                };
+
+                let slot: &mut Option<(usize, i64)> = &mut best[child.parent];
+
+                if slot.is_none_or(|(_, best)| best <= score) {
+                    *slot = Some((i, score));
+                }
+            }
+
+            best
+        })
+        .collect();
+
+    let mut lines = vec![Vec::new(); layers[0].len()];
+
+    for (mut i, node) in layers[0].iter().enumerate() {
+        let line = &mut lines[node.index];
+
+        line.extend(node.step);
+
+        for (depth, best) in best.iter().enumerate() {
+            let Some((child, _)) = best[i] else {
+                break;
+            };
+
+            i = child;
+            line.extend(layers[depth + 1][i].step);
+        }
     }
-    selector.clear();
 
-    nodes
+    lines
 }
 
 fn is_queue_valid(queue: &[Piece], mut bag: Bag) -> bool {
@@ -268,3 +966,341 @@ impl PartialOrd for BotScore {
         Some(self.cmp(other))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use tetris::piece::Rotation;
+
+    use super::*;
+
+    const CONFIGS: BotConfigs = BotConfigs {
+        width: 16,
+        depth: 6,
+        branch: 0,
+        threads: 1,
+    };
+
+    fn bot() -> BotState {
+        let queue = vec![
+            Piece::I,
+            Piece::O,
+            Piece::L,
+            Piece::J,
+            Piece::S,
+            Piece::Z,
+            Piece::T,
+        ];
+        let lock = Lock::default();
+
+        BotState::new(State::new(), lock, queue, Weights::default()).unwrap()
+    }
+
+    #[test]
+    fn depth() {
+        let configs = BotConfigs {
+            depth: 3,
+            ..CONFIGS
+        };
+
+        assert_eq!(bot().search(configs).unwrap().depth, 3);
+        assert_eq!(bot().search(CONFIGS).unwrap().depth, 6);
+    }
+
+    #[test]
+    fn speculate() {
+        let mut state = State::new();
+
+        state.bag.remove(Piece::I);
+        state.bag.remove(Piece::O);
+
+        let queue = [Piece::I, Piece::O];
+
+        assert!(chances(&queue, &state).is_empty());
+
+        state.next = 1;
+
+        assert_eq!(chances(&queue, &state), Bag::all() - Piece::I - Piece::O);
+
+        state.next = 2;
+        state.bag.remove(Piece::L);
+
+        assert_eq!(chances(&queue, &state), state.bag);
+
+        let lock = Lock::default();
+        let mut bot =
+            BotState::new(State::new(), lock, queue.to_vec(), Weights::default()).unwrap();
+        let configs = BotConfigs {
+            depth: 4,
+            ..CONFIGS
+        };
+        let result = bot.search(configs).unwrap();
+        let best = result.candidates.iter().max_by_key(|c| c.score).unwrap();
+
+        assert_eq!(result.depth, 4);
+
+        // Only the two known pieces can be placed for sure, the rest of the line is a guess
+        assert!(best.line[0].chance.is_none());
+        assert!(best.line[2..].iter().all(|step| step.chance.is_some()));
+    }
+
+    #[test]
+    fn hold_guess() {
+        // The O ends its bag, so the piece after it may be another O
+        let queue = [Piece::I, Piece::O];
+        let mut state = State::new();
+
+        state.bag = Bag::empty() | Piece::O;
+        state.next = 1;
+
+        let context = Context {
+            queue: &queue,
+            incoming: &[],
+            evaluator: &Weights::default(),
+            limit: None,
+            branch: 0,
+            threads: 1,
+        };
+        let node = Node::new(state, Lock::default());
+        let mut holds = Vec::new();
+
+        expand(&context, &node, 1, |child, _| {
+            if child.step.unwrap().hold {
+                holds.push(child);
+            }
+        });
+
+        // Every hold keeps the O, also when the guess is an O as well
+        assert!(holds.iter().all(|child| child.state.hold == Some(Piece::O)));
+        assert!(holds.iter().all(|child| child.state.next == 3));
+        assert!(
+            holds
+                .iter()
+                .any(|child| child.step.unwrap().chance == Some(Piece::O))
+        );
+    }
+
+    #[test]
+    fn expectimax() {
+        // Every node guesses between an I and an O
+        let mut state = State::new();
+
+        state.bag = Bag::empty() | Piece::I | Piece::O;
+
+        let node = |parent, chance, value| Node {
+            value,
+            parent,
+            step: Some(Step {
+                mv: Move {
+                    x: 4,
+                    y: 0,
+                    r: Rotation::North,
+                    kind: Piece::T,
+                    tspin: None,
+                },
+                hold: false,
+                chance,
+            }),
+            ..Node::new(state.clone(), Lock::default())
+        };
+        let mut candidates = vec![Candidate {
+            mv: node(0, None, 0).step.unwrap().mv,
+            score: BotScore { depth: 0, score: 0 },
+            line: Vec::new(),
+            terms: Vec::new(),
+        }];
+
+        // Two speculated layers under the root move, worst first like the beam keeps them
+        let layers = vec![
+            vec![node(0, None, 0)],
+            vec![node(0, Some(Piece::O), -30), node(0, Some(Piece::I), 0)],
+            vec![
+                node(1, Some(Piece::O), -20),
+                node(1, Some(Piece::I), 0),
+                node(0, Some(Piece::O), 0),
+                node(0, Some(Piece::I), 0),
+            ],
+        ];
+        let scores = vec![Some(-200), Some(100), Some(300), Some(-100)];
+        let values = backup(&[], &layers, scores, &mut candidates, 3);
+
+        // The O branch averages to 100 and the I branch to -50, picking the best at every layer
+        // would have claimed 300
+        assert_eq!(
+            candidates[0].score,
+            BotScore {
+                depth: 3,
+                score: 25
+            }
+        );
+
+        // The line goes through the O branch to its O that scored 300, the beam ranked the I last
+        let line = &lines(&layers, &values)[0];
+
+        assert_eq!(
+            line.iter().map(|step| step.chance).collect::<Vec<_>>(),
+            [None, Some(Piece::O), Some(Piece::O)]
+        );
+
+        // A piece with nothing left in the beam counts as the worst node kept there
+        let scores = vec![Some(-200), Some(100), None, None];
+
+        backup(&[], &layers, scores, &mut candidates, 3);
+
+        assert_eq!(
+            candidates[0].score,
+            BotScore {
+                depth: 3,
+                score: -40
+            }
+        );
+    }
+
+    #[test]
+    fn branch() {
+        let wide = BotConfigs {
+            width: 1000,
+            depth: 3,
+            branch: 0,
+            threads: 1,
+        };
+        let narrow = BotConfigs { branch: 1, ..wide };
+
+        assert!(bot().search(narrow).unwrap().nodes < bot().search(wide).unwrap().nodes);
+    }
+
+    #[test]
+    fn threads() {
+        let single = bot().search(CONFIGS).unwrap();
+        let configs = BotConfigs {
+            threads: 4,
+            ..CONFIGS
+        };
+        let first = bot().search(configs).unwrap();
+        let second = bot().search(configs).unwrap();
+
+        assert_eq!(first.candidates, second.candidates);
+        assert_eq!(first.nodes, second.nodes);
+        assert_eq!(first.depth, single.depth);
+    }
+
+    #[test]
+    fn nodes() {
+        let full = bot().search(CONFIGS).unwrap();
+        let budget = Budget {
+            limit: Limit::Nodes(full.nodes / 2),
+            adaptive: false,
+        };
+        let limited = bot().search_within(CONFIGS, budget).unwrap();
+
+        assert!(limited.depth < full.depth);
+        assert_eq!(limited.candidates.len(), full.candidates.len());
+    }
+
+    #[test]
+    fn deadline() {
+        let budget = Budget {
+            limit: Limit::Deadline(Instant::now()),
+            adaptive: false,
+        };
+
+        assert_eq!(bot().search_within(CONFIGS, budget).unwrap().depth, 1);
+    }
+
+    #[test]
+    fn adaptive() {
+        let full = bot().search(CONFIGS).unwrap();
+        let budget = Budget {
+            limit: Limit::Nodes(full.nodes * 4),
+            adaptive: true,
+        };
+        let grown = bot().search_within(CONFIGS, budget).unwrap();
+
+        assert_eq!(grown.depth, full.depth);
+        assert!(grown.nodes > full.nodes);
+    }
+
+    #[test]
+    fn reuse() {
+        let mut reused = bot();
+        let mut fresh = bot();
+
+        let mv = best_move(&reused.search(CONFIGS).unwrap()).unwrap();
+        let consumed = match mv.kind == Piece::I {
+            true => 1,
+            false => 2,
+        };
+        let pieces = &[Piece::I, Piece::O][..consumed];
+
+        reused.make(mv, pieces).unwrap();
+        fresh.make(mv, pieces).unwrap();
+
+        assert_eq!(reused.root(), fresh.root());
+        assert!(!reused.tree.is_empty());
+
+        let reused = reused.search(CONFIGS).unwrap();
+        let fresh = fresh.search(CONFIGS).unwrap();
+
+        assert_eq!(reused.depth, fresh.depth);
+        assert!(reused.nodes < fresh.nodes);
+
+        // Landing garbage throws the old tree away
+        let mut bot = bot();
+
+        bot.search(CONFIGS).unwrap();
+        bot.reset(Board::new(), 0, 0).unwrap();
+
+        assert!(bot.tree.is_empty());
+    }
+
+    #[test]
+    fn line() {
+        let configs = BotConfigs {
+            depth: 5,
+            ..CONFIGS
+        };
+        let bot = bot();
+        let result = bot.clone().search(configs).unwrap();
+        let best = result.candidates.iter().max_by_key(|c| c.score).unwrap();
+
+        assert_eq!(best.line.len(), result.depth);
+        assert_eq!(best.line[0].mv, best.mv);
+        assert!(!best.terms.is_empty());
+
+        // The line must be playable from the root in order
+        let mut state = bot.root().clone();
+
+        for step in &best.line {
+            let current = bot.queue()[state.next];
+
+            assert_eq!(step.hold, step.mv.kind != current);
+            assert!(movegen(&state.board, step.mv.kind).contains(&step.mv));
+
+            state.make(&step.mv, bot.queue());
+        }
+    }
+
+    #[test]
+    fn evaluator() {
+        // Only cares about keeping the stack low
+        struct Flat;
+
+        impl Evaluator for Flat {
+            fn evaluate(&self, node: &mut Node, _: Move) {
+                node.value = -(node.state.board.heights().into_iter().max().unwrap() as i32);
+            }
+        }
+
+        let lock = Lock::default();
+        let configs = BotConfigs {
+            depth: 1,
+            ..CONFIGS
+        };
+        let mut bot = BotState::new(State::new(), lock, vec![Piece::O, Piece::I], Flat).unwrap();
+        let mv = best_move(&bot.search(configs).unwrap()).unwrap();
+        let mut board = Board::new();
+
+        board.place(&mv);
+
+        assert_eq!(board.heights().into_iter().max(), Some(1));
+    }
+}
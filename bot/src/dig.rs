@@ -0,0 +1,114 @@
+use tetris::moves::Move;
+
+use crate::{
+    eval::{Evaluator, Part, Term, Weights},
+    node::Node,
+    preset::Preset,
+};
+
+// Scoring for digging: the stack shape of the dig preset and a price on every garbage row still at
+// the bottom of the board
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Dig {
+    pub weights: Weights,
+    pub garbage: i32,
+}
+
+impl Default for Dig {
+    fn default() -> Self {
+        Self {
+            weights: Preset::Dig.weights(),
+            garbage: -400,
+        }
+    }
+}
+
+impl Dig {
+    fn term(&self, node: &Node) -> Term {
+        let left = node.garbage as i32;
+
+        Term {
+            name: "garbage_left",
+            part: Part::Value,
+            feature: left,
+            score: left * self.garbage,
+        }
+    }
+}
+
+impl Evaluator for Dig {
+    fn evaluate(&self, node: &mut Node, mv: Move) {
+        self.weights.evaluate(node, mv);
+        self.term(node).apply(node);
+    }
+
+    fn explain(&self, node: &mut Node, mv: Move) -> Vec<Term> {
+        let mut explained = self.weights.explain(node, mv);
+        let term = self.term(node);
+
+        term.apply(node);
+        explained.push(term);
+        explained
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tetris::{
+        piece::{Piece, Rotation},
+        state::{Lock, State},
+    };
+
+    use super::*;
+    use crate::garbage;
+
+    #[test]
+    fn garbage() {
+        let dig = Dig::default();
+        let mut state = State::new();
+
+        // Two lines of garbage sharing a hole in column 1, the bottom one missing column 0 too
+        state.board.add_garbage(2, 1);
+        state.board.clear(0, 0);
+
+        let mv = Move {
+            x: 1,
+            y: 2,
+            r: Rotation::East,
+            kind: Piece::I,
+            tspin: None,
+        };
+        let mut node = Node {
+            garbage: 2,
+            ..Node::new(state, Lock::default())
+        };
+
+        node.garbage = garbage::dig(node.garbage, &node.state.board, &mv);
+        node.lock = node.state.make(&mv, &[Piece::I, Piece::T]);
+
+        // Only the top line is full, the bottom one still misses column 0
+        assert_eq!(node.lock.cleared, 1);
+        assert_eq!(node.garbage, 1);
+
+        let terms = dig.explain(&mut node.clone(), mv);
+        let left = terms
+            .iter()
+            .find(|term| term.name == "garbage_left")
+            .unwrap();
+
+        assert_eq!(left.feature, 1);
+
+        let mut evaluated = node.clone();
+
+        dig.evaluate(&mut evaluated, mv);
+
+        assert_eq!(
+            evaluated.value,
+            terms
+                .iter()
+                .filter(|term| term.part == Part::Value)
+                .map(|term| term.score)
+                .sum::<i32>()
+        );
+    }
+}
@@ -1,14 +1,119 @@
-use std::u64;
+use std::sync::LazyLock;
 
+use serde::{Deserialize, Serialize};
 use tetris::{
     board::Board,
     moves::{Move, Tspin},
     piece::{Piece, Rotation},
 };
 
-use crate::node::Node;
+use crate::{bot::BotError, node::Node};
 
-#[derive(Debug, Clone, Copy)]
+pub const SETUPS: usize = 5;
+
+// Term names of the setups ready to spin and of those a piece short, apart from the tslot terms
+// that only count slots by the lines they clear
+pub const SETUP_NAMES: [&str; SETUPS] = [
+    "tst_ready",
+    "stsd_ready",
+    "fin_ready",
+    "neo_ready",
+    "imperial_ready",
+];
+pub const ALMOST_NAMES: [&str; SETUPS] = [
+    "tst_unfinished",
+    "stsd_unfinished",
+    "fin_unfinished",
+    "neo_unfinished",
+    "imperial_unfinished",
+];
+
+// How many tslots in a row we try to clear before judging the rest of the stack, two stopped right
+// after a stsd and never saw the slot its clears open up below
+pub const DONATIONS: usize = 3;
+
+// Cells an almost finished setup may still be missing, one more piece
+const ALMOST: u32 = 4;
+
+// Spin setups drawn from the top row down: `T` is where the T ends, `#` must be filled, `.` must be
+// empty and `?` is anything, the listed rows (counted from the bottom) are the ones the T clears
+const TEMPLATES: [(usize, &[&str], &[u32]); SETUPS] = [
+    (0, &["...", "#T#", "#TT", "#T#"], &[0, 1, 2]),
+    (1, &["...", "#T#", "#TT", "#T#"], &[0, 1]),
+    (2, &["##.", "#T.", "#TT", "#T#"], &[0, 1]),
+    (3, &["##..", "TTT#", "#T##"], &[0, 1]),
+    (4, &["?.?", "#T#", "TTT", "###"], &[1, 2]),
+];
+
+struct Pattern {
+    setup: usize,
+    width: u32,
+    // Rows the T clears: the row and its T cells as a column mask, every other cell must be filled
+    clears: Vec<(u32, u16)>,
+    // Cells on the other rows: column, row and whether the cell must be filled
+    cells: Vec<(u32, u32, bool)>,
+}
+
+// Every template along with its mirror image
+static PATTERNS: LazyLock<Vec<Pattern>> = LazyLock::new(|| {
+    let mut patterns = Vec::new();
+
+    for (setup, rows, clears) in TEMPLATES {
+        for mirror in [false, true] {
+            let width = rows[0].len() as u32;
+            let mut pattern = Pattern {
+                setup,
+                width,
+                clears: clears.iter().map(|&y| (y, 0)).collect(),
+                cells: Vec::new(),
+            };
+
+            for (i, row) in rows.iter().enumerate() {
+                let y = (rows.len() - 1 - i) as u32;
+                let clear = pattern.clears.iter_mut().find(|(row, _)| *row == y);
+
+                match clear {
+                    Some((_, mask)) => {
+                        for (x, cell) in row.chars().enumerate() {
+                            let x = if mirror {
+                                width - 1 - x as u32
+                            } else {
+                                x as u32
+                            };
+
+                            if cell == 'T' {
+                                *mask |= 1 << x;
+                            }
+                        }
+                    }
+                    None => {
+                        for (x, cell) in row.chars().enumerate() {
+                            let x = if mirror {
+                                width - 1 - x as u32
+                            } else {
+                                x as u32
+                            };
+
+                            match cell {
+                                '#' => pattern.cells.push((x, y, true)),
+                                'T' | '.' => pattern.cells.push((x, y, false)),
+                                _ => {}
+                            }
+                        }
+                    }
+                }
+            }
+
+            patterns.push(pattern);
+        }
+    }
+
+    patterns
+});
+
+// Missing fields fall back to the defaults so a file only needs the weights it changes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct Weights {
     pub height: i32,
     pub well: i32,
@@ -16,7 +121,10 @@ pub struct Weights {
     pub bumpiness: i32,
     pub holes: i32,
     pub garbage: i32,
+    pub pending: i32,
     pub tslot: [i32; 4],
+    pub setup: [i32; SETUPS],
+    pub almost: [i32; SETUPS],
     pub b2b_bonus: i32,
     pub combo_bonus: i32,
 
@@ -27,6 +135,14 @@ pub struct Weights {
     pub b2b: i32,
     pub pc: i32,
     pub waste_t: i32,
+
+    // Finer stack shape, off unless asked for so existing weights keep scoring the same
+    pub row_transitions: i32,
+    pub col_transitions: i32,
+    pub covered: i32,
+    pub hole_depth: i32,
+    pub overhangs: i32,
+    pub quad_ready: i32,
 }
 
 impl Default for Weights {
@@ -38,7 +154,11 @@ impl Default for Weights {
             bumpiness: -25,
             holes: -400,
             garbage: -300,
+            pending: -30,
             tslot: [150, 200, 250, 500],
+            // Looking for setups slows the search down a lot, presets that build spins turn them on
+            setup: [0; SETUPS],
+            almost: [0; SETUPS],
             b2b_bonus: 200,
             combo_bonus: 200,
 
@@ -49,12 +169,118 @@ impl Default for Weights {
             b2b: 100,
             pc: 2000,
             waste_t: -100,
+
+            row_transitions: 0,
+            col_transitions: 0,
+            covered: 0,
+            hole_depth: 0,
+            overhangs: 0,
+            quad_ready: 0,
+        }
+    }
+}
+
+impl Weights {
+    pub fn from_json(json: &str) -> Result<Self, BotError> {
+        serde_json::from_str(json).map_err(|error| BotError::InvalidWeights(error.to_string()))
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("weights always serialize")
+    }
+
+    // Every weight as a flat list, in declaration order
+    pub fn params_mut(&mut self) -> Vec<&mut i32> {
+        let mut params = vec![
+            &mut self.height,
+            &mut self.well,
+            &mut self.center,
+            &mut self.bumpiness,
+            &mut self.holes,
+            &mut self.garbage,
+            &mut self.pending,
+        ];
+
+        params.extend(&mut self.tslot);
+        params.extend(&mut self.setup);
+        params.extend(&mut self.almost);
+        params.extend([&mut self.b2b_bonus, &mut self.combo_bonus]);
+        params.extend(&mut self.clear);
+        params.extend(&mut self.tspin);
+        params.extend(&mut self.tspin_mini);
+        params.extend(&mut self.combo);
+        params.extend([&mut self.b2b, &mut self.pc, &mut self.waste_t]);
+        params.extend([
+            &mut self.row_transitions,
+            &mut self.col_transitions,
+            &mut self.covered,
+            &mut self.hole_depth,
+            &mut self.overhangs,
+            &mut self.quad_ready,
+        ]);
+
+        params
+    }
+
+    pub fn params(&self) -> Vec<i32> {
+        let mut weights = *self;
+
+        weights.params_mut().into_iter().map(|p| *p).collect()
+    }
+
+    // Move every weight `t` of the way towards the other set
+    pub fn lerp(&self, other: &Weights, t: f32) -> Weights {
+        let mut weights = *self;
+
+        weights.zip_mut(other, |weight, target| {
+            *weight += ((target - *weight) as f32 * t).round() as i32;
+        });
+
+        weights
+    }
+
+    // Visit every weight along with the same one of the other set, without collecting them first
+    // as `params_mut` does since blending runs for every node
+    fn zip_mut(&mut self, other: &Weights, mut f: impl FnMut(&mut i32, i32)) {
+        let scalars = [
+            (&mut self.height, other.height),
+            (&mut self.well, other.well),
+            (&mut self.center, other.center),
+            (&mut self.bumpiness, other.bumpiness),
+            (&mut self.holes, other.holes),
+            (&mut self.garbage, other.garbage),
+            (&mut self.pending, other.pending),
+            (&mut self.b2b_bonus, other.b2b_bonus),
+            (&mut self.combo_bonus, other.combo_bonus),
+            (&mut self.b2b, other.b2b),
+            (&mut self.pc, other.pc),
+            (&mut self.waste_t, other.waste_t),
+            (&mut self.row_transitions, other.row_transitions),
+            (&mut self.col_transitions, other.col_transitions),
+            (&mut self.covered, other.covered),
+            (&mut self.hole_depth, other.hole_depth),
+            (&mut self.overhangs, other.overhangs),
+            (&mut self.quad_ready, other.quad_ready),
+        ];
+
+        let pairs = scalars
+            .into_iter()
+            .chain(self.tslot.iter_mut().zip(other.tslot))
+            .chain(self.setup.iter_mut().zip(other.setup))
+            .chain(self.almost.iter_mut().zip(other.almost))
+            .chain(self.clear.iter_mut().zip(other.clear))
+            .chain(self.tspin.iter_mut().zip(other.tspin))
+            .chain(self.tspin_mini.iter_mut().zip(other.tspin_mini))
+            .chain(self.combo.iter_mut().zip(other.combo));
+
+        for (weight, target) in pairs {
+            f(weight, target);
         }
     }
 }
 
 // Return the well's depth and the position of the well
-fn well(board: &Board, heights: &[u32; 10]) -> (i32, usize) {
+pub fn well(board: &Board, heights: &[u32; 10]) -> (i32, usize) {
     let mut x = 0;
 
     for i in 1..10 {
@@ -78,7 +304,7 @@ fn well(board: &Board, heights: &[u32; 10]) -> (i32, usize) {
     (mask.count_ones() as i32, x)
 }
 
-fn bumpiness(heights: &[u32; 10], well_x: usize) -> i32 {
+pub fn bumpiness(heights: &[u32; 10], well_x: usize) -> i32 {
     let mut bumpiness = 0;
     let mut left = 0;
 
@@ -101,104 +327,158 @@ fn bumpiness(heights: &[u32; 10], well_x: usize) -> i32 {
 }
 
 // Get the number of holes overground and underground
-fn holes(board: &Board, heights: &[u32; 10], well_x: usize) -> (i32, i32) {
+pub fn holes(board: &Board, heights: &[u32; 10], well_x: usize) -> (i32, i32) {
     let min_height = heights[well_x];
 
     let mut holes = 0;
 
-    for i in 0..10 {
-        holes += heights[i] - min_height - (board.cols[i] >> min_height).count_ones();
+    for (height, col) in heights.iter().zip(board.cols.iter()) {
+        holes += height - min_height - (col >> min_height).count_ones();
     }
 
     (holes as i32, min_height as i32)
 }
 
+// Cells below the given height
+fn below(height: u32) -> u64 {
+    u64::MAX.checked_shr(64 - height).unwrap_or(0)
+}
+
+// Get the number of filled and empty neighbours along the rows and down the columns, the walls
+// count as filled and so does the floor
+pub fn transitions(board: &Board, heights: &[u32; 10]) -> (i32, i32) {
+    let stack = below(*heights.iter().max().unwrap());
+
+    let mut rows = (!board.cols[0] & stack).count_ones() + (!board.cols[9] & stack).count_ones();
+
+    for x in 0..9 {
+        rows += ((board.cols[x] ^ board.cols[x + 1]) & stack).count_ones();
+    }
+
+    let mut cols = 0;
+
+    for (height, col) in heights.iter().zip(board.cols.iter()) {
+        cols += ((col ^ (col << 1 | 1)) & below(*height)).count_ones();
+    }
+
+    (rows as i32, cols as i32)
+}
+
+// Get the number of filled cells above the lowest hole of each column and how deep every hole is
+// buried below the top of its column
+pub fn covered(board: &Board, heights: &[u32; 10]) -> (i32, i32) {
+    let mut covered = 0;
+    let mut depth = 0;
+
+    for (&height, col) in heights.iter().zip(board.cols.iter()) {
+        let mut holes = !col & below(height);
+
+        if holes == 0 {
+            continue;
+        }
+
+        covered += (col >> holes.trailing_zeros()).count_ones();
+
+        while holes != 0 {
+            depth += height - 1 - holes.trailing_zeros();
+            holes &= holes - 1;
+        }
+    }
+
+    (covered as i32, depth as i32)
+}
+
+// Get the number of filled cells with an empty cell right below
+pub fn overhangs(board: &Board) -> i32 {
+    board
+        .cols
+        .iter()
+        .map(|col| (col & !(col << 1 | 1)).count_ones() as i32)
+        .sum()
+}
+
 // Find the highest tslot
 fn tslot(board: &Board, heights: &[u32; 10]) -> Option<Move> {
     for x in 0..8 {
-        if heights[x] > heights[x + 1] && heights[x] + 1 < heights[x + 2] {
-            if ((board.cols[x] >> (heights[x] - 1)) & 0b111) == 0b001
-                && ((board.cols[x + 1] >> (heights[x] - 1)) & 0b111) == 0b000
-                && ((board.cols[x + 2] >> (heights[x] - 1)) & 0b111) == 0b101
-            {
-                return Some(Move {
-                    x: x as i8 + 1,
-                    y: heights[x] as i8,
-                    r: Rotation::South,
-                    kind: Piece::T,
-                    tspin: None,
-                });
-            }
+        if heights[x] > heights[x + 1]
+            && heights[x] + 1 < heights[x + 2]
+            && ((board.cols[x] >> (heights[x] - 1)) & 0b111) == 0b001
+            && ((board.cols[x + 1] >> (heights[x] - 1)) & 0b111) == 0b000
+            && ((board.cols[x + 2] >> (heights[x] - 1)) & 0b111) == 0b101
+        {
+            return Some(Move {
+                x: x as i8 + 1,
+                y: heights[x] as i8,
+                r: Rotation::South,
+                kind: Piece::T,
+                tspin: None,
+            });
         }
 
-        if heights[x + 2] > heights[x + 1] && heights[x + 2] + 1 < heights[x] {
-            if ((board.cols[x] >> (heights[x + 2] - 1)) & 0b111) == 0b101
-                && ((board.cols[x + 1] >> (heights[x + 2] - 1)) & 0b111) == 0b000
-                && ((board.cols[x + 2] >> (heights[x + 2] - 1)) & 0b111) == 0b001
-            {
-                return Some(Move {
-                    x: x as i8 + 1,
-                    y: heights[x + 2] as i8,
-                    r: Rotation::South,
-                    kind: Piece::T,
-                    tspin: None,
-                });
-            }
+        if heights[x + 2] > heights[x + 1]
+            && heights[x + 2] + 1 < heights[x]
+            && ((board.cols[x] >> (heights[x + 2] - 1)) & 0b111) == 0b101
+            && ((board.cols[x + 1] >> (heights[x + 2] - 1)) & 0b111) == 0b000
+            && ((board.cols[x + 2] >> (heights[x + 2] - 1)) & 0b111) == 0b001
+        {
+            return Some(Move {
+                x: x as i8 + 1,
+                y: heights[x + 2] as i8,
+                r: Rotation::South,
+                kind: Piece::T,
+                tspin: None,
+            });
         }
 
         if heights[x + 1] >= 3
             && heights[x + 1] >= heights[x]
             && heights[x + 1] + 1 < heights[x + 2]
+            && ((board.cols[x] >> (heights[x + 1] - 3)) & 0b11000) == 0b00000
+            && ((board.cols[x + 1] >> (heights[x + 1] - 3)) & 0b11110) == 0b00100
+            && ((board.cols[x + 2] >> (heights[x + 1] - 3)) & 0b11111) == 0b10000
+            && (board.has(x as i8 + 1, heights[x + 1] as i8 - 3)
+                || (!board.has(x as i8 + 1, heights[x + 1] as i8 - 3)
+                    && board.has(x as i8 + 2, heights[x + 1] as i8 - 4)))
         {
-            if ((board.cols[x] >> (heights[x + 1] - 3)) & 0b11000) == 0b00000
-                && ((board.cols[x + 1] >> (heights[x + 1] - 3)) & 0b11110) == 0b00100
-                && ((board.cols[x + 2] >> (heights[x + 1] - 3)) & 0b11111) == 0b10000
-                && (board.has(x as i8 + 1, heights[x + 1] as i8 - 3)
-                    || (!board.has(x as i8 + 1, heights[x + 1] as i8 - 3)
-                        && board.has(x as i8 + 2, heights[x + 1] as i8 - 4)))
-            {
-                return Some(Move {
-                    x: x as i8 + 2,
-                    y: heights[x + 1] as i8 - 2,
-                    r: Rotation::West,
-                    kind: Piece::T,
-                    tspin: None,
-                });
-            }
+            return Some(Move {
+                x: x as i8 + 2,
+                y: heights[x + 1] as i8 - 2,
+                r: Rotation::West,
+                kind: Piece::T,
+                tspin: None,
+            });
         }
 
         if heights[x + 1] >= 3
             && heights[x + 1] >= heights[x + 2]
             && heights[x + 1] + 1 < heights[x]
+            && ((board.cols[x] >> (heights[x + 1] - 3)) & 0b11111) == 0b10000
+            && ((board.cols[x + 1] >> (heights[x + 1] - 3)) & 0b11110) == 0b00100
+            && ((board.cols[x + 2] >> (heights[x + 1] - 3)) & 0b11000) == 0b00000
+            && (board.has(x as i8 + 1, heights[x + 1] as i8 - 3)
+                || (!board.has(x as i8 + 1, heights[x + 1] as i8 - 3)
+                    && board.has(x as i8, heights[x + 1] as i8 - 4)))
         {
-            if ((board.cols[x] >> (heights[x + 1] - 3)) & 0b11111) == 0b10000
-                && ((board.cols[x + 1] >> (heights[x + 1] - 3)) & 0b11110) == 0b00100
-                && ((board.cols[x + 2] >> (heights[x + 1] - 3)) & 0b11000) == 0b00000
-                && (board.has(x as i8 + 1, heights[x + 1] as i8 - 3)
-                    || (!board.has(x as i8 + 1, heights[x + 1] as i8 - 3)
-                        && board.has(x as i8, heights[x + 1] as i8 - 4)))
-            {
-                return Some(Move {
-                    x: x as i8,
-                    y: heights[x + 1] as i8 - 2,
-                    r: Rotation::East,
-                    kind: Piece::T,
-                    tspin: None,
-                });
-            }
+            return Some(Move {
+                x: x as i8,
+                y: heights[x + 1] as i8 - 2,
+                r: Rotation::East,
+                kind: Piece::T,
+                tspin: None,
+            });
         }
     }
 
     None
 }
 
-fn donations(board: &mut Board, heights: &mut [u32; 10], depth: usize) -> ([i32; 4], i32) {
+pub fn donations(board: &mut Board, heights: &mut [u32; 10], depth: usize) -> ([i32; 4], i32) {
     let mut tslots = [0; 4];
     let mut donations = 0;
 
     for _ in 0..depth {
         if let Some(tslot) = tslot(board, heights) {
-            let mut clone = board.clone();
+            let mut clone = *board;
 
             clone.place(&tslot);
 
@@ -220,92 +500,555 @@ fn donations(board: &mut Board, heights: &mut [u32; 10], depth: usize) -> ([i32;
     (tslots, donations)
 }
 
+// Count the spin setups that are ready and those that are one piece away, per kind
+pub fn setups(board: &Board) -> ([i32; SETUPS], [i32; SETUPS]) {
+    let mut ready = [0; SETUPS];
+    let mut almost = [0; SETUPS];
+
+    let height = board
+        .cols
+        .iter()
+        .map(|col| 64 - col.leading_zeros())
+        .max()
+        .unwrap() as usize;
+
+    // Per row, the empty columns
+    let mut empty = [0x3ff_u16; 64];
+
+    empty[..height].fill(0);
+
+    let below = u64::MAX.checked_shr(64 - height as u32).unwrap_or(0);
+
+    for (x, col) in board.cols.iter().enumerate() {
+        let mut holes = !col & below;
+
+        while holes != 0 {
+            empty[holes.trailing_zeros() as usize] |= 1 << x;
+            holes &= holes - 1;
+        }
+    }
+
+    // Rows whose gaps fit in a pattern of some width, by how many gaps they have at least
+    let mut rows = [[0u64; 5]; 5];
+
+    for (y, &gaps) in empty[..height].iter().enumerate() {
+        if gaps == 0 {
+            continue;
+        }
+
+        let span = 16 - gaps.leading_zeros() - gaps.trailing_zeros();
+
+        for counts in rows.iter_mut().skip(span.max(3) as usize) {
+            for count in counts.iter_mut().take(gaps.count_ones() as usize + 1) {
+                *count |= 1 << y;
+            }
+        }
+    }
+
+    // Above the stack in its column, so a later piece can still fill it
+    let open = |x: u32, y: u32| board.cols[x as usize].checked_shr(y).unwrap_or(0) == 0;
+
+    for pattern in PATTERNS.iter() {
+        let (bottom, mask) = pattern.clears[0];
+        let window = (1u16 << pattern.width) - 1;
+
+        // Every row the T clears needs a gap for each of its cells
+        let mut bases = u64::MAX;
+
+        for &(dy, mask) in &pattern.clears {
+            bases &= rows[pattern.width as usize][mask.count_ones() as usize] >> dy;
+        }
+
+        while bases != 0 {
+            let base = bases.trailing_zeros() as usize;
+
+            bases &= bases - 1;
+
+            let gaps = empty[base + bottom as usize];
+            let low = gaps.trailing_zeros();
+            let high = 15 - gaps.leading_zeros();
+
+            // The window has to cover every gap of the row
+            let first = (high + 1).saturating_sub(pattern.width);
+            let last = low.min(10 - pattern.width);
+
+            if first > last {
+                continue;
+            }
+
+            // Anchors where the bottom row also has room for the T
+            let mut anchors = ((1u16 << (last + 1)) - 1) & !((1u16 << first) - 1);
+
+            for x in 0..pattern.width {
+                if mask >> x & 1 != 0 {
+                    anchors &= gaps >> x;
+                }
+            }
+
+            while anchors != 0 {
+                let x = anchors.trailing_zeros();
+
+                anchors &= anchors - 1;
+
+                let mut missing = 0;
+
+                // The cleared rows must be full but for the T, and gaps above the stack we can fill
+                let cleared = pattern.clears.iter().all(|&(dy, mask)| {
+                    let row = base + dy as usize;
+                    let t = mask << x;
+                    let rest = empty[row] & !t;
+
+                    missing += rest.count_ones();
+
+                    if empty[row] & t != t || rest & !(window << x) != 0 {
+                        return false;
+                    }
+
+                    let mut gaps = rest;
+
+                    while gaps != 0 {
+                        if !open(gaps.trailing_zeros(), row as u32) {
+                            return false;
+                        }
+
+                        gaps &= gaps - 1;
+                    }
+
+                    true
+                });
+
+                if !cleared {
+                    continue;
+                }
+
+                let fits = pattern.cells.iter().all(|&(dx, dy, filled)| {
+                    let col = board.cols[(x + dx) as usize]
+                        .checked_shr(base as u32 + dy)
+                        .unwrap_or(0);
+
+                    match (filled, col & 1 != 0) {
+                        (true, false) if col == 0 => {
+                            missing += 1;
+                            true
+                        }
+                        (true, has) | (false, has) => has == filled,
+                    }
+                });
+
+                match (fits, missing) {
+                    (true, 0) => ready[pattern.setup] += 1,
+                    (true, 1..=ALMOST) => almost[pattern.setup] += 1,
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    (ready, almost)
+}
+
+// Scores a freshly placed child, setting its value and adding the move's reward to its own
+pub trait Evaluator: Send + Sync {
+    fn evaluate(&self, node: &mut Node, mv: Move);
+
+    // Evaluate the child and list the terms that went into it, evaluators without a breakdown
+    // give nothing
+    fn explain(&self, node: &mut Node, mv: Move) -> Vec<Term> {
+        self.evaluate(node, mv);
+        Vec::new()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Part {
+    Value,
+    Reward,
+}
+
+// One evaluation term: the raw feature and what it added after weighting
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Term {
+    pub name: &'static str,
+    pub part: Part,
+    pub feature: i32,
+    pub score: i32,
+}
+
+impl Term {
+    pub fn apply(&self, node: &mut Node) {
+        match self.part {
+            Part::Value => node.value += self.score,
+            Part::Reward => node.reward += self.score,
+        }
+    }
+}
+
+impl Evaluator for Weights {
+    fn evaluate(&self, node: &mut Node, mv: Move) {
+        evaluate(node, mv, self);
+    }
+
+    fn explain(&self, node: &mut Node, mv: Move) -> Vec<Term> {
+        let mut explained = Vec::new();
+
+        score(node, mv, self, |term| explained.push(term));
+        explained
+    }
+}
+
 pub fn evaluate(node: &mut Node, mv: Move, w: &Weights) {
-    node.value = 0;
+    score(node, mv, w, |_| {});
+}
+
+// Add the terms up into the node, handing each one over on the way
+fn score(node: &mut Node, mv: Move, w: &Weights, mut each: impl FnMut(Term)) {
+    let mut value = 0;
+    let mut reward = 0;
+
+    terms(node, mv, w, |term| {
+        match term.part {
+            Part::Value => value += term.score,
+            Part::Reward => reward += term.score,
+        }
+
+        each(term);
+    });
+
+    node.value = value;
+    node.reward += reward;
+}
+
+// Report every term of the evaluation, the value ones always and the reward ones when they apply
+fn terms(node: &Node, mv: Move, w: &Weights, mut term: impl FnMut(Term)) {
+    let mut value = |name, feature: i32, weight: i32| {
+        term(Term {
+            name,
+            part: Part::Value,
+            feature,
+            score: feature * weight,
+        })
+    };
 
-    let mut board = node.state.board.clone();
+    let mut board = node.state.board;
     let mut heights = board.heights();
 
     // Height
     let height = *heights.iter().max().unwrap() as i32;
 
-    node.value += height * w.height;
+    value("height", height, w.height);
 
     // Tslots
-    let (tslots, donations) = donations(&mut board, &mut heights, 2);
+    // Looking for setups is the costliest part, so it is skipped when they carry no weight
+    let (ready, almost) = match w.setup == [0; SETUPS] && w.almost == [0; SETUPS] {
+        true => ([0; SETUPS], [0; SETUPS]),
+        false => setups(&board),
+    };
+    let (tslots, donations) = donations(&mut board, &mut heights, DONATIONS);
+
+    for (name, (count, weight)) in ["tslot0", "tslot1", "tslot2", "tslot3"]
+        .into_iter()
+        .zip(tslots.iter().zip(w.tslot.iter()))
+    {
+        value(name, *count, *weight);
+    }
+
+    // Spin setups
+    for (i, name) in SETUP_NAMES.into_iter().enumerate() {
+        value(name, ready[i], w.setup[i]);
+    }
 
-    for i in 0..4 {
-        node.value += tslots[i] * w.tslot[i];
+    for (i, name) in ALMOST_NAMES.into_iter().enumerate() {
+        value(name, almost[i], w.almost[i]);
     }
 
     // Well
     let (well, well_x) = well(&board, &heights);
 
-    node.value += well.min(4) * w.well;
+    value("well", well.min(4), w.well);
+    value("quad_ready", (well >= 4) as i32, w.quad_ready);
 
     // Center
-    node.value += well_x.abs_diff(4).min(well_x.abs_diff(5)) as i32 * w.center;
+    value(
+        "center",
+        well_x.abs_diff(4).min(well_x.abs_diff(5)) as i32,
+        w.center,
+    );
 
     // Bumpiness
     let bumpiness = bumpiness(&heights, well_x);
 
-    node.value += bumpiness * w.bumpiness;
+    value("bumpiness", bumpiness, w.bumpiness);
 
     // Holes
     let (mut holes, garbage) = holes(&board, &heights, well_x);
 
     holes -= tslots[0] + tslots[1] + tslots[2] + tslots[3] - donations;
 
-    node.value += holes * w.holes;
-    node.value += garbage * w.garbage;
+    value("holes", holes, w.holes);
+    value("garbage", garbage, w.garbage);
+
+    // Shape of the stack around its holes, each scan skipped like the setups when it carries no
+    // weight
+    let (row_transitions, col_transitions) = match w.row_transitions == 0 && w.col_transitions == 0
+    {
+        true => (0, 0),
+        false => transitions(&board, &heights),
+    };
+    let (covered, hole_depth) = match w.covered == 0 && w.hole_depth == 0 {
+        true => (0, 0),
+        false => covered(&board, &heights),
+    };
+    let overhangs = match w.overhangs == 0 {
+        true => 0,
+        false => overhangs(&board),
+    };
+
+    value("row_transitions", row_transitions, w.row_transitions);
+    value("col_transitions", col_transitions, w.col_transitions);
+    value("covered", covered, w.covered);
+    value("hole_depth", hole_depth, w.hole_depth);
+    value("overhangs", overhangs, w.overhangs);
+
+    // Incoming garbage that is neither cancelled nor landed yet
+    value("pending", node.pending as i32, w.pending);
 
     // Bonus
-    if node.state.b2b > 0 {
-        node.value += w.b2b_bonus;
-    }
+    value("b2b_bonus", (node.state.b2b > 0) as i32, w.b2b_bonus);
+    value(
+        "combo_bonus",
+        (node.state.combo as i32 - 1).max(0),
+        w.combo_bonus,
+    );
 
-    if node.state.combo > 1 {
-        node.value += (node.state.combo as i32 - 1) * w.combo_bonus;
-    }
+    let mut reward = |name, feature: i32, score: i32| {
+        term(Term {
+            name,
+            part: Part::Reward,
+            feature,
+            score,
+        })
+    };
 
     // Pc
     let pc = board.is_empty();
 
     if pc {
-        node.reward += w.pc;
+        reward("pc", 1, w.pc);
     }
 
     // Line clear
-    if node.lock.cleared > 0 {
+    let cleared = node.lock.cleared as i32;
+
+    if cleared > 0 {
         if pc {
-            node.reward += w.pc;
+            reward("pc_clear", cleared, w.pc);
         } else if let Some(tspin) = mv.tspin {
-            node.reward += match tspin {
-                Tspin::Full => w.tspin[node.lock.cleared as usize - 1],
-                Tspin::Mini => w.tspin_mini[node.lock.cleared as usize - 1],
-            };
+            match tspin {
+                Tspin::Full => reward("tspin", cleared, w.tspin[cleared as usize - 1]),
+                Tspin::Mini => reward("tspin_mini", cleared, w.tspin_mini[cleared as usize - 1]),
+            }
         } else {
-            node.reward += w.clear[node.lock.cleared as usize - 1];
+            reward("clear", cleared, w.clear[cleared as usize - 1]);
         }
     }
 
     // Back to back
     if node.state.b2b > 1 {
-        node.reward += w.b2b;
+        reward("b2b", node.state.b2b as i32, w.b2b);
     }
 
     // Combo
-    node.reward += match node.state.combo {
-        0..2 => 0,
-        2..4 => w.combo[0],
-        4..6 => w.combo[1],
-        6..8 => w.combo[2],
-        8..10 => w.combo[3],
-        _ => w.combo[4],
+    let combo = match node.state.combo {
+        0..2 => None,
+        2..4 => Some(w.combo[0]),
+        4..6 => Some(w.combo[1]),
+        6..8 => Some(w.combo[2]),
+        8..10 => Some(w.combo[3]),
+        _ => Some(w.combo[4]),
     };
 
+    if let Some(score) = combo {
+        reward("combo", node.state.combo as i32, score);
+    }
+
     // Waste T
     if mv.kind == Piece::T && !(mv.tspin.is_some() && node.lock.cleared > 0) && !pc {
-        node.reward += w.waste_t;
+        reward("waste_t", 1, w.waste_t);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tetris::state::{Lock, State};
+
+    use super::*;
+
+    #[test]
+    fn json() {
+        let weights = Weights::default();
+
+        assert_eq!(Weights::from_json(&weights.to_json()).unwrap(), weights);
+    }
+
+    #[test]
+    fn partial() {
+        let weights = Weights::from_json(r#"{ "holes": -1, "clear": [1, 2, 3, 4] }"#).unwrap();
+
+        assert_eq!(weights.holes, -1);
+        assert_eq!(weights.clear, [1, 2, 3, 4]);
+        assert_eq!(weights.height, Weights::default().height);
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(Weights::from_json(r#"{ "tslot": [1, 2, 3] }"#).is_err());
+        assert!(Weights::from_json(r#"{ "combo": [1, 2, 3, 4, 5, 6] }"#).is_err());
+        assert!(Weights::from_json(r#"{ "hieght": -50 }"#).is_err());
+    }
+
+    #[test]
+    fn explain() {
+        let weights = Weights::default();
+        let lock = Lock {
+            cleared: 1,
+            ..Lock::default()
+        };
+        let mut node = Node {
+            reward: 100,
+            pending: 2,
+            ..Node::new(State::new(), lock)
+        };
+
+        node.state.board.add_garbage(2, 3);
+        node.state.combo = 3;
+
+        let mv = Move {
+            x: 4,
+            y: 2,
+            r: Rotation::North,
+            kind: Piece::T,
+            tspin: None,
+        };
+        let mut explained = node.clone();
+        let terms = weights.explain(&mut explained, mv);
+        let sum = |part| {
+            terms
+                .iter()
+                .filter(|term| term.part == part)
+                .map(|term| term.score)
+                .sum::<i32>()
+        };
+
+        let mut evaluated = node.clone();
+
+        evaluate(&mut evaluated, mv, &weights);
+
+        // The breakdown adds up to exactly what the evaluation gives, and explaining evaluates too
+        assert_eq!(sum(Part::Value), evaluated.value);
+        assert_eq!(sum(Part::Reward), evaluated.reward - node.reward);
+        assert_eq!(
+            (explained.value, explained.reward),
+            (evaluated.value, evaluated.reward)
+        );
+
+        let term = |name| terms.iter().find(|term| term.name == name).copied();
+
+        assert_eq!(term("height").unwrap().feature, 2);
+        assert_eq!(term("pending").unwrap().score, 2 * weights.pending);
+        assert_eq!(term("clear").unwrap().score, weights.clear[0]);
+        assert_eq!(term("combo").unwrap().score, weights.combo[0]);
+        assert!(term("waste_t").is_some());
+        assert!(term("pc").is_none());
+        assert_eq!(term("tst_ready").unwrap().score, 0);
+        assert_eq!(term("tst_unfinished").unwrap().score, 0);
+
+        // The finer shape terms are off unless a weight file turns them on
+        for name in [
+            "row_transitions",
+            "col_transitions",
+            "covered",
+            "hole_depth",
+            "overhangs",
+            "quad_ready",
+        ] {
+            assert_eq!(term(name).unwrap().score, 0, "{name}");
+        }
+
+        // Every term goes by its own name
+        let mut names: Vec<_> = terms.iter().map(|term| term.name).collect();
+
+        names.sort();
+        names.dedup();
+
+        assert_eq!(names.len(), terms.len());
+
+        // A perfect clear is reported apart from the lines it cleared
+        let mut pc = node.clone();
+
+        pc.state.board = Board::new();
+
+        let terms = weights.explain(&mut pc, mv);
+        let term = |name| terms.iter().find(|term| term.name == name).copied();
+
+        assert_eq!(term("pc").unwrap().score, weights.pc);
+        assert_eq!(term("pc_clear").unwrap().feature, 1);
+        assert!(term("clear").is_none());
+    }
+
+    #[test]
+    fn shape() {
+        let mut board = Board::new();
+
+        // A column of three with a hole at the bottom and another under its top cell
+        board.cols[0] = 0b1010;
+        board.cols[1] = 0b1111;
+
+        let heights = board.heights();
+
+        // Rows with a hole change four times between the walls, the others twice
+        assert_eq!(transitions(&board, &heights), (12, 4));
+        assert_eq!(covered(&board, &heights), (2, 4));
+        assert_eq!(overhangs(&board), 2);
+    }
+
+    #[test]
+    fn setups() {
+        for (setup, rows, clears) in TEMPLATES {
+            // Draw the template with its left edge on column 2 and fill the rows it clears
+            let mut board = Board::new();
+
+            for (i, row) in rows.iter().enumerate() {
+                let y = (rows.len() - 1 - i) as i8;
+
+                for (x, cell) in row.chars().enumerate() {
+                    if cell == '#' {
+                        board.set(x as i8 + 2, y);
+                    }
+                }
+
+                if clears.contains(&(y as u32)) {
+                    for x in (0..2).chain(2 + row.len() as i8..10) {
+                        board.set(x, y);
+                    }
+                }
+            }
+
+            let (ready, _) = super::setups(&board);
+
+            assert!(ready[setup] > 0, "{}", SETUP_NAMES[setup]);
+
+            // Take away the top of one column in the pattern, a piece could put it back
+            let x = (2..2 + rows[0].len())
+                .find(|&x| board.cols[x] != 0)
+                .unwrap();
+            let y = board.height(x) as i8 - 1;
+
+            board.clear(x as i8, y);
+
+            let (ready, almost) = super::setups(&board);
+
+            assert_eq!(ready[setup], 0, "{}", SETUP_NAMES[setup]);
+            assert!(almost[setup] > 0, "{}", ALMOST_NAMES[setup]);
+        }
     }
 }
@@ -0,0 +1,51 @@
+use tetris::state::State;
+
+use crate::eval::{DONATIONS, bumpiness, donations, holes, well};
+
+pub const FEATURES: usize = 14;
+
+// Position summary for learned models, every entry is scaled to stay roughly within 0..1
+pub fn features(state: &State, pending: u16) -> [f32; FEATURES] {
+    let mut board = state.board;
+    let mut heights = board.heights();
+
+    let height = *heights.iter().max().unwrap();
+    let (tslots, donations) = donations(&mut board, &mut heights, DONATIONS);
+    let (well, well_x) = well(&board, &heights);
+    let center = well_x.abs_diff(4).min(well_x.abs_diff(5));
+    let bumpiness = bumpiness(&heights, well_x);
+    let (mut holes, garbage) = holes(&board, &heights, well_x);
+
+    holes -= tslots.iter().sum::<i32>() - donations;
+
+    [
+        height as f32 / 20.0,
+        well.min(4) as f32 / 4.0,
+        center as f32 / 4.0,
+        bumpiness as f32 / 100.0,
+        holes as f32 / 10.0,
+        garbage as f32 / 10.0,
+        tslots[0] as f32,
+        tslots[1] as f32,
+        tslots[2] as f32,
+        tslots[3] as f32,
+        state.b2b.min(10) as f32 / 10.0,
+        state.combo.min(10) as f32 / 10.0,
+        pending.min(20) as f32 / 20.0,
+        board.is_empty() as u8 as f32,
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty() {
+        let features = features(&State::new(), 0);
+
+        assert_eq!(features[0], 0.0);
+        assert_eq!(features[4], 0.0);
+        assert_eq!(features[FEATURES - 1], 1.0);
+    }
+}
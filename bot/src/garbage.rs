@@ -0,0 +1,123 @@
+use tetris::{board::Board, moves::Move};
+
+use crate::node::Node;
+
+// An attack waiting to land, delay is counted in pieces placed by the receiver
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Garbage {
+    pub lines: u8,
+    pub delay: usize,
+}
+
+pub fn total(incoming: &[Garbage]) -> u16 {
+    incoming.iter().map(|g| g.lines as u16).sum()
+}
+
+// Garbage rows still at the bottom once the move is placed, those it fills clear
+pub fn dig(rows: u8, board: &Board, mv: &Move) -> u8 {
+    if rows == 0 {
+        return 0;
+    }
+
+    let mut board = *board;
+
+    board.place(mv);
+
+    let bottom = u64::MAX >> (64 - rows.min(64) as u32);
+
+    rows - (board.full() & bottom).count_ones() as u8
+}
+
+// Cancel the child's pending garbage with its attack, then land what has arrived by this depth,
+// returning false when that pushes the stack under the spawn past the top
+pub fn receive(node: &mut Node, incoming: &[Garbage], depth: usize) -> bool {
+    node.pending -= node.pending.min(node.lock.sent as u16);
+
+    if node.lock.cleared > 0 || node.pending == 0 {
+        return true;
+    }
+
+    let consumed = total(incoming) - node.pending;
+    let arrived: u16 = incoming
+        .iter()
+        .filter(|g| g.delay <= depth)
+        .map(|g| g.lines as u16)
+        .sum();
+    let lines = arrived.saturating_sub(consumed);
+
+    if lines == 0 {
+        return true;
+    }
+
+    // The hole can land anywhere, so plan for it buried under our tallest column
+    let heights = node.state.board.heights();
+    let hole = (0..10).max_by_key(|&x| heights[x]).unwrap();
+
+    node.state.board.add_garbage(lines as u8, hole);
+    node.pending -= lines;
+    node.garbage = node.garbage.saturating_add(lines as u8);
+
+    node.state.board.heights()[3..7].iter().all(|&h| h <= 20)
+}
+
+#[cfg(test)]
+mod tests {
+    use tetris::state::{Lock, State};
+
+    use super::*;
+
+    fn node(sent: u8, cleared: u8, pending: u16) -> Node {
+        Node {
+            pending,
+            ..Node::new(
+                State::new(),
+                Lock {
+                    cleared,
+                    sent,
+                    softdrop: false,
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn receive() {
+        let incoming = [
+            Garbage { lines: 2, delay: 1 },
+            Garbage { lines: 3, delay: 3 },
+        ];
+
+        // Attack cancels the front of the queue
+        let mut cancel = node(3, 2, 5);
+
+        assert!(super::receive(&mut cancel, &incoming, 1));
+        assert_eq!(cancel.pending, 2);
+        assert!(cancel.state.board.is_empty());
+
+        // Only what has arrived lands
+        let mut land = node(0, 0, 5);
+
+        assert!(super::receive(&mut land, &incoming, 1));
+        assert_eq!(land.pending, 3);
+        assert_eq!(land.state.board.heights().iter().max(), Some(&2));
+
+        // The hole goes under the tallest column
+        land.state.board.set(4, 2);
+
+        assert!(super::receive(&mut land, &incoming, 3));
+        assert_eq!(land.pending, 0);
+        assert_eq!(land.state.board.count(), 46);
+        assert_eq!(land.garbage, 5);
+        assert!(!land.state.board.has(4, 2));
+        assert!(land.state.board.has(4, 5));
+
+        // Garbage lifting the stack under the spawn past the top ends the game
+        let mut dead = node(0, 0, 3);
+
+        for y in 0..19 {
+            dead.state.board.set(5, y);
+        }
+
+        assert!(!super::receive(&mut dead, &incoming, 3));
+    }
+}
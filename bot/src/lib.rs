@@ -1,4 +1,12 @@
+pub mod blend;
+pub mod book;
 pub mod bot;
+pub mod dig;
 pub mod eval;
+pub mod features;
+pub mod garbage;
+pub mod model;
 pub mod node;
+pub mod preset;
 pub mod selector;
+pub mod sprint;
@@ -0,0 +1,165 @@
+use serde::{Deserialize, Serialize};
+use tetris::moves::Move;
+
+use crate::{
+    bot::BotError,
+    eval::Evaluator,
+    features::{FEATURES, features},
+    node::Node,
+};
+
+// Model outputs are in lines of attack, node scores in hundredths of a line
+const SCALE: f32 = 100.0;
+
+// A dense layer, `weights` holds one row of inputs per output
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Layer {
+    pub weights: Vec<Vec<f32>>,
+    pub bias: Vec<f32>,
+}
+
+// A small MLP over the position features predicting the attack still to come, relu between layers
+// and a single linear output, so one layer alone is a linear model
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Model {
+    layers: Vec<Layer>,
+}
+
+impl Layer {
+    pub fn zeros(inputs: usize, outputs: usize) -> Self {
+        Self {
+            weights: vec![vec![0.0; inputs]; outputs],
+            bias: vec![0.0; outputs],
+        }
+    }
+
+    pub fn inputs(&self) -> usize {
+        self.weights.first().map_or(0, Vec::len)
+    }
+
+    pub fn outputs(&self) -> usize {
+        self.bias.len()
+    }
+
+    pub fn forward(&self, input: &[f32]) -> Vec<f32> {
+        self.weights
+            .iter()
+            .zip(&self.bias)
+            .map(|(row, bias)| row.iter().zip(input).map(|(w, x)| w * x).sum::<f32>() + bias)
+            .collect()
+    }
+}
+
+impl Model {
+    pub fn new(layers: Vec<Layer>) -> Result<Self, BotError> {
+        let shape = |message: &str| Err(BotError::InvalidModel(message.to_string()));
+
+        if layers.is_empty() {
+            return shape("no layers");
+        }
+
+        if layers[0].inputs() != FEATURES {
+            return shape("first layer must take every feature");
+        }
+
+        if layers.last().unwrap().outputs() != 1 {
+            return shape("last layer must have a single output");
+        }
+
+        for (i, layer) in layers.iter().enumerate() {
+            if layer.outputs() != layer.weights.len()
+                || layer.weights.iter().any(|row| row.len() != layer.inputs())
+            {
+                return shape(&format!("layer {i} is not rectangular"));
+            }
+
+            if i > 0 && layer.inputs() != layers[i - 1].outputs() {
+                return shape(&format!("layer {i} doesn't match the layer before"));
+            }
+        }
+
+        Ok(Self { layers })
+    }
+
+    // A linear model that predicts nothing, a starting point for training
+    pub fn linear() -> Self {
+        Self {
+            layers: vec![Layer::zeros(FEATURES, 1)],
+        }
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, BotError> {
+        let layers = serde_json::from_str(json)
+            .map_err(|error| BotError::InvalidModel(error.to_string()))?;
+
+        Self::new(layers)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(&self.layers).expect("layers always serialize")
+    }
+
+    pub fn layers(&self) -> &[Layer] {
+        &self.layers
+    }
+
+    pub fn layers_mut(&mut self) -> &mut [Layer] {
+        &mut self.layers
+    }
+
+    pub fn predict(&self, input: &[f32]) -> f32 {
+        let mut values = input.to_vec();
+
+        for (i, layer) in self.layers.iter().enumerate() {
+            values = layer.forward(&values);
+
+            if i + 1 < self.layers.len() {
+                values.iter_mut().for_each(|v| *v = v.max(0.0));
+            }
+        }
+
+        values[0]
+    }
+}
+
+// The position is worth the attack it is predicted to send, on top of what the path already sent
+impl Evaluator for Model {
+    fn evaluate(&self, node: &mut Node, _: Move) {
+        let input = features(&node.state, node.pending);
+
+        node.value = (self.predict(&input) * SCALE) as i32;
+        node.reward += node.lock.sent as i32 * SCALE as i32;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn predict() {
+        let mut hidden = Layer::zeros(FEATURES, 2);
+        let mut output = Layer::zeros(2, 1);
+
+        hidden.weights[0][0] = 1.0;
+        hidden.weights[1][0] = -1.0;
+        output.weights[0] = vec![2.0, 3.0];
+        output.bias[0] = 0.5;
+
+        let model = Model::new(vec![hidden, output]).unwrap();
+        let mut input = [0.0; FEATURES];
+
+        input[0] = 1.0;
+
+        // The second hidden unit is negative and cut by the relu
+        assert_eq!(model.predict(&input), 2.5);
+        assert_eq!(Model::from_json(&model.to_json()).unwrap(), model);
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(Model::new(vec![Layer::zeros(3, 1)]).is_err());
+        assert!(Model::new(vec![Layer::zeros(FEATURES, 2)]).is_err());
+        assert!(Model::from_json("{}").is_err());
+    }
+}
@@ -1,5 +1,18 @@
 use std::cmp::Ordering;
-use tetris::state::{Lock, State};
+use tetris::{
+    moves::Move,
+    piece::Piece,
+    state::{Lock, State},
+};
+
+// A placement and whether the piece came out of hold, along with the unseen piece it was guessed
+// with when the queue didn't reach that far
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Step {
+    pub mv: Move,
+    pub hold: bool,
+    pub chance: Option<Piece>,
+}
 
 #[derive(Debug, Clone)]
 pub struct Node {
@@ -8,6 +21,33 @@ pub struct Node {
     pub value: i32,
     pub reward: i32,
     pub index: usize,
+    // Position of the parent in the previous layer of the beam
+    pub parent: usize,
+    // The placement that led here, none for the root
+    pub step: Option<Step>,
+    pub pending: u16,
+    // Garbage rows left at the bottom of the board, for evaluators that dig
+    pub garbage: u8,
+    // Lines cleared on the way down from the root, for evaluators racing to a line count
+    pub lines: u16,
+}
+
+impl Node {
+    // Nothing scored and no placement yet, as for the root of a search
+    pub fn new(state: State, lock: Lock) -> Self {
+        Self {
+            state,
+            lock,
+            value: 0,
+            reward: 0,
+            index: 0,
+            parent: 0,
+            step: None,
+            pending: 0,
+            garbage: 0,
+            lines: 0,
+        }
+    }
 }
 
 impl PartialOrd for Node {
@@ -0,0 +1,135 @@
+use strum::{EnumIter, EnumString, IntoStaticStr};
+
+use crate::eval::Weights;
+
+// Named weight sets for the ways we play, picked by their lowercase name
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter, EnumString, IntoStaticStr)]
+#[strum(serialize_all = "lowercase")]
+pub enum Preset {
+    Aggressive,
+    Defensive,
+    Combo,
+    Pc,
+    Sprint,
+    Dig,
+}
+
+impl Preset {
+    pub fn name(self) -> &'static str {
+        self.into()
+    }
+
+    pub fn weights(self) -> Weights {
+        let default = Weights::default();
+
+        match self {
+            // Versus attack: the default stack built toward spin setups, with spins and back to back
+            // worth a lot more
+            Preset::Aggressive => Weights {
+                setup: [300, 200, 200, 150, 150],
+                almost: [100, 50, 50, 50, 50],
+                b2b_bonus: 300,
+                tspin: [100, 600, 1200],
+                b2b: 200,
+                ..default
+            },
+            // Survival: keep the stack low and clean, clear whatever is on offer and cancel
+            // incoming garbage rather than build for attack
+            Preset::Defensive => Weights {
+                height: -150,
+                bumpiness: -40,
+                holes: -600,
+                pending: -80,
+                tslot: [50, 50, 50, 100],
+                b2b_bonus: 50,
+                clear: [100, 150, 200, 400],
+                row_transitions: -10,
+                col_transitions: -20,
+                covered: -40,
+                hole_depth: -30,
+                overhangs: -50,
+                ..default
+            },
+            // Chain clears: every line is worth taking as long as the combo keeps going
+            Preset::Combo => Weights {
+                height: -30,
+                well: 0,
+                center: 0,
+                tslot: [0; 4],
+                b2b_bonus: 0,
+                combo_bonus: 600,
+                clear: [150, 150, 150, 300],
+                combo: [800, 1500, 2500, 3500, 4500],
+                waste_t: 0,
+                ..default
+            },
+            // Perfect clears: a flat stack with no holes, holding clears back until the whole board
+            // can go at once
+            Preset::Pc => Weights {
+                height: -20,
+                well: 0,
+                center: 0,
+                bumpiness: -40,
+                holes: -1000,
+                tslot: [0; 4],
+                b2b_bonus: 0,
+                clear: [-200; 4],
+                tspin: [0; 3],
+                b2b: 0,
+                pc: 10000,
+                waste_t: 0,
+                row_transitions: -10,
+                col_transitions: -20,
+                covered: -60,
+                hole_depth: -10,
+                overhangs: -50,
+                ..default
+            },
+            // 40 lines: every clear counts the same, so keep flat and low and clear as it comes
+            Preset::Sprint => Weights {
+                height: -100,
+                center: 0,
+                holes: -800,
+                tslot: [0; 4],
+                b2b_bonus: 0,
+                clear: [100, 200, 300, 400],
+                tspin: [100, 200, 300],
+                b2b: 0,
+                waste_t: 0,
+                ..default
+            },
+            // Cheese: clear down to the floor, garbage lines and buried holes cost the most
+            Preset::Dig => Weights {
+                height: -50,
+                holes: -700,
+                garbage: -600,
+                tslot: [0; 4],
+                b2b_bonus: 0,
+                clear: [200, 300, 400, 500],
+                waste_t: 0,
+                row_transitions: -10,
+                col_transitions: -20,
+                covered: -60,
+                hole_depth: -40,
+                overhangs: -50,
+                ..default
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use strum::IntoEnumIterator;
+
+    use super::*;
+
+    #[test]
+    fn names() {
+        for preset in Preset::iter() {
+            assert_eq!(preset.name().parse::<Preset>().unwrap(), preset);
+        }
+
+        assert!("fast".parse::<Preset>().is_err());
+    }
+}
@@ -18,10 +18,18 @@ impl Selector {
         Self {
             map: HashMap::with_capacity(1 << 12),
             heap: BinaryHeap::with_capacity(size),
-            size: size,
+            size,
         }
     }
 
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn set_size(&mut self, size: usize) {
+        self.size = size;
+    }
+
     pub fn clear(&mut self) {
         self.map.clear();
         self.heap.clear();
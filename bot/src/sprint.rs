@@ -0,0 +1,148 @@
+use tetris::{finesse::finesse, moves::Move};
+
+use crate::{
+    eval::{Evaluator, Part, Term, Weights},
+    node::Node,
+    preset::Preset,
+};
+
+// Scoring for a race to clear some lines: the stack shape of the sprint preset with every attack
+// term dropped, then the lines cleared, presses spent, and cells stacked past the last line that
+// will never be cleared
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sprint {
+    pub weights: Weights,
+    // Lines still to clear from the root
+    pub lines: u32,
+    pub line: i32,
+    pub input: i32,
+    pub waste: i32,
+}
+
+impl Sprint {
+    pub fn new(lines: u32) -> Self {
+        Self {
+            weights: Weights {
+                b2b_bonus: 0,
+                combo_bonus: 0,
+                clear: [0; 4],
+                tspin: [0; 3],
+                tspin_mini: [0; 2],
+                combo: [0; 5],
+                b2b: 0,
+                pc: 0,
+                waste_t: 0,
+                ..Preset::Sprint.weights()
+            },
+            lines,
+            line: 300,
+            input: -20,
+            waste: -50,
+        }
+    }
+
+    fn terms(&self, node: &Node, mv: Move) -> [Term; 3] {
+        let cleared = node.lock.cleared as i32;
+
+        // A soft drop costs the press itself and at least one more to slide in, a hold one press
+        let inputs = finesse(&mv) as i32
+            + 2 * node.lock.softdrop as i32
+            + node.step.is_some_and(|step| step.hold) as i32;
+
+        // Rows above the lines still left at this node are never cleared
+        let left = self.lines.saturating_sub(node.lines as u32);
+        let wasted = node
+            .state
+            .board
+            .cols
+            .iter()
+            .map(|col| col.checked_shr(left).unwrap_or(0).count_ones() as i32)
+            .sum::<i32>();
+
+        let term = |name, part, feature, weight| Term {
+            name,
+            part,
+            feature,
+            score: feature * weight,
+        };
+
+        [
+            term("lines", Part::Reward, cleared, self.line),
+            term("inputs", Part::Reward, inputs, self.input),
+            term("wasted", Part::Value, wasted, self.waste),
+        ]
+    }
+}
+
+impl Evaluator for Sprint {
+    fn evaluate(&self, node: &mut Node, mv: Move) {
+        self.weights.evaluate(node, mv);
+
+        for term in self.terms(node, mv) {
+            term.apply(node);
+        }
+    }
+
+    fn explain(&self, node: &mut Node, mv: Move) -> Vec<Term> {
+        let mut explained = self.weights.explain(node, mv);
+
+        for term in self.terms(node, mv) {
+            term.apply(node);
+            explained.push(term);
+        }
+
+        explained
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tetris::{
+        piece::{Piece, Rotation},
+        state::{Lock, State},
+    };
+
+    use super::*;
+
+    #[test]
+    fn terms() {
+        let sprint = Sprint::new(2);
+        let lock = Lock {
+            cleared: 1,
+            ..Lock::default()
+        };
+        let mut node = Node {
+            lines: 1,
+            ..Node::new(State::new(), lock)
+        };
+
+        node.state.board.cols[0] = 0b111;
+
+        let mv = Move {
+            x: 0,
+            y: 1,
+            r: Rotation::East,
+            kind: Piece::I,
+            tspin: None,
+        };
+        let terms = sprint.explain(&mut node.clone(), mv);
+        let term = |name| terms.iter().find(|term| term.name == name).unwrap().feature;
+
+        assert_eq!(term("lines"), 1);
+        assert_eq!(term("inputs"), 3);
+        // The node already cleared one of the two lines, so only the bottom row is still needed
+        assert_eq!(term("wasted"), 2);
+
+        // The clear is worth its lines and nothing for the attack it sends
+        assert!(terms.iter().any(|t| t.name == "clear" && t.score == 0));
+
+        let mut evaluated = node.clone();
+
+        sprint.evaluate(&mut evaluated, mv);
+
+        assert_eq!(
+            evaluated.value + evaluated.reward,
+            terms.iter().map(|term| term.score).sum::<i32>()
+        );
+    }
+}
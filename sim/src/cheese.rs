@@ -0,0 +1,215 @@
+use rand::{Rng, SeedableRng, rngs::StdRng};
+
+use bot::{bot::BotConfigs, dig::Dig};
+use tetris::board::Board;
+
+use crate::{
+    player::Player,
+    replay::{Mode, Replay},
+};
+
+#[derive(Debug, Clone, Copy)]
+pub struct CheeseConfigs {
+    // Garbage lines in the whole race, and how many of them are on the board at once
+    pub lines: usize,
+    pub rows: usize,
+    // Chance the hole moves from one line to the next
+    pub change: f64,
+    // Chance every other cell of a line is left out as well
+    pub messiness: f64,
+    pub previews: usize,
+    pub max_pieces: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct CheeseResult {
+    pub finished: bool,
+    pub pieces: usize,
+    pub garbage: usize,
+    pub replay: Replay,
+}
+
+// Seeded garbage lines, handed out until the race has none left
+#[derive(Debug, Clone)]
+pub struct Cheese {
+    rng: StdRng,
+    configs: CheeseConfigs,
+    hole: usize,
+    left: usize,
+}
+
+impl Default for CheeseConfigs {
+    fn default() -> Self {
+        Self {
+            lines: 100,
+            rows: 10,
+            change: 1.0,
+            messiness: 0.0,
+            previews: 5,
+            max_pieces: 1000,
+        }
+    }
+}
+
+impl CheeseResult {
+    pub fn pieces_per_line(&self) -> f64 {
+        self.pieces as f64 / self.garbage.max(1) as f64
+    }
+}
+
+impl Cheese {
+    pub fn new(configs: CheeseConfigs, seed: u64) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let hole = rng.random_range(0..10);
+
+        Self {
+            rng,
+            configs,
+            hole,
+            left: configs.lines,
+        }
+    }
+
+    pub fn left(&self) -> usize {
+        self.left
+    }
+
+    // Push the next line under the board, false once the race is out of lines
+    pub fn push(&mut self, board: &mut Board) -> bool {
+        if self.left == 0 {
+            return false;
+        }
+
+        // A moving hole always lands on another column
+        if self.rng.random_bool(self.configs.change) {
+            self.hole = (self.hole + self.rng.random_range(1..10)) % 10;
+        }
+
+        for (x, col) in board.cols.iter_mut().enumerate() {
+            *col <<= 1;
+
+            if x != self.hole && !self.rng.random_bool(self.configs.messiness) {
+                *col |= 1;
+            }
+        }
+
+        self.left -= 1;
+
+        true
+    }
+
+    // A fresh board with as many lines as the race shows at once
+    pub fn board(&mut self) -> Board {
+        let mut board = Board::new();
+
+        for _ in 0..self.configs.rows {
+            self.push(&mut board);
+        }
+
+        board
+    }
+}
+
+// Dig through the lines of a seeded cheese race, topping the board back up as lines are cleared
+pub fn cheese(
+    evaluator: Dig,
+    bot_configs: BotConfigs,
+    seed: u64,
+    configs: &CheeseConfigs,
+) -> CheeseResult {
+    let mut cheese = Cheese::new(*configs, seed);
+    let mut player = Player::new(evaluator, bot_configs, seed, configs.previews);
+    let mut garbage = 0;
+
+    let board = cheese.board();
+
+    player.replay.rules.mode = Mode::Cheese;
+    player.reset(board, 0, 0);
+    player
+        .bot
+        .set_garbage(configs.rows.min(configs.lines) as u8);
+
+    while garbage < configs.lines && player.stats.pieces < configs.max_pieces {
+        let before = player.bot.garbage();
+
+        if player.play().is_err() {
+            break;
+        }
+
+        let root = player.bot.root();
+        let mut left = player.bot.garbage();
+
+        garbage += (before - left) as usize;
+
+        if left < before && cheese.left() > 0 {
+            let (mut board, b2b, combo) = (root.board, root.b2b, root.combo);
+
+            while (left as usize) < configs.rows && cheese.push(&mut board) {
+                left += 1;
+            }
+
+            player.reset(board, b2b, combo);
+            player.bot.set_garbage(left);
+        }
+    }
+
+    CheeseResult {
+        finished: garbage >= configs.lines,
+        pieces: player.stats.pieces,
+        garbage,
+        replay: player.replay,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn push() {
+        let configs = CheeseConfigs {
+            lines: 12,
+            ..Default::default()
+        };
+        let mut cheese = Cheese::new(configs, 0);
+        let mut board = cheese.board();
+
+        // Every line has a single hole and no two lines in a row share it
+        let holes: Vec<_> = (0..10)
+            .map(|y| {
+                (0..10)
+                    .filter(|&x| board.cols[x] >> y & 1 == 0)
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+
+        assert!(holes.iter().all(|holes| holes.len() == 1));
+        assert!(holes.windows(2).all(|pair| pair[0] != pair[1]));
+
+        assert!(cheese.push(&mut board));
+        assert!(cheese.push(&mut board));
+        assert!(!cheese.push(&mut board));
+        assert_eq!(board.count(), 12 * 9);
+    }
+
+    #[test]
+    fn cheese() {
+        let bot_configs = BotConfigs {
+            width: 16,
+            depth: 3,
+            branch: 0,
+            threads: 1,
+        };
+        let configs = CheeseConfigs {
+            lines: 6,
+            rows: 4,
+            ..Default::default()
+        };
+        let result = super::cheese(Dig::default(), bot_configs, 0, &configs);
+
+        // Every line takes at least one piece to fill its hole
+        assert!(result.finished);
+        assert_eq!(result.garbage, 6);
+        assert!(result.pieces >= 6);
+    }
+}
@@ -0,0 +1,9 @@
+pub mod cheese;
+pub mod parallel;
+pub mod player;
+pub mod randomizer;
+pub mod replay;
+pub mod solo;
+pub mod sprint;
+pub mod sprt;
+pub mod versus;
@@ -0,0 +1,322 @@
+use std::time::{Duration, Instant};
+
+use rand::Rng;
+
+use bot::{
+    bot::{BotConfigs, BotError, BotState, best_move},
+    eval::{Evaluator, Weights},
+    garbage::Garbage,
+};
+use tetris::{
+    board::Board,
+    moves::Move,
+    state::{Lock, State},
+};
+
+use crate::{
+    randomizer::Randomizer,
+    replay::{Record, Replay, Rules, Search},
+};
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Stats {
+    pub pieces: usize,
+    pub attack: usize,
+    pub cleared: usize,
+    pub received: usize,
+    pub tspins: usize,
+    pub quads: usize,
+    pub pcs: usize,
+    // Back to back clears in a row right now, and the longest run so far
+    pub b2b: usize,
+    pub max_b2b: usize,
+    pub max_combo: usize,
+    // Nodes searched and time spent searching over every move
+    pub nodes: usize,
+    pub time: Duration,
+}
+
+#[derive(Debug, Clone)]
+pub struct Player<E = Weights> {
+    pub bot: BotState<E>,
+    pub configs: BotConfigs,
+    pub randomizer: Randomizer,
+    pub garbage: Vec<Garbage>,
+    pub stats: Stats,
+    pub replay: Replay,
+}
+
+impl Stats {
+    pub fn app(&self) -> f64 {
+        self.attack as f64 / self.pieces.max(1) as f64
+    }
+
+    pub fn lpp(&self) -> f64 {
+        self.cleared as f64 / self.pieces.max(1) as f64
+    }
+}
+
+impl<E: Evaluator> Player<E> {
+    pub fn new(evaluator: E, configs: BotConfigs, seed: u64, previews: usize) -> Self {
+        let mut randomizer = Randomizer::new(seed);
+        let queue = randomizer.by_ref().take(previews + 1).collect();
+        let lock = Lock::default();
+        let bot = BotState::new(State::new(), lock, queue, evaluator)
+            .expect("bag randomizer always gives a valid queue");
+        let rules = Rules {
+            previews,
+            ..Default::default()
+        };
+        let replay = Replay::new(rules, seed, bot.root().clone());
+
+        Self {
+            bot,
+            configs,
+            randomizer,
+            garbage: Vec::new(),
+            stats: Stats::default(),
+            replay,
+        }
+    }
+
+    pub fn pending(&self) -> u32 {
+        self.garbage.iter().map(|g| g.lines as u32).sum()
+    }
+
+    // Search and play the next piece, refilling the queue with what the move consumed
+    pub fn play(&mut self) -> Result<(Move, Lock), BotError> {
+        self.bot.set_incoming(&self.garbage);
+
+        let start = Instant::now();
+        let result = self.bot.search(self.configs)?;
+        let mv = best_move(&result)?;
+        let elapsed = start.elapsed();
+
+        self.stats.time += elapsed;
+        self.stats.nodes += result.nodes;
+
+        let hold = mv.kind != self.bot.queue()[0];
+        let consumed = match hold && self.bot.root().hold.is_none() {
+            true => 2,
+            false => 1,
+        };
+        let pieces: Vec<_> = self.randomizer.by_ref().take(consumed).collect();
+
+        let lock = self.bot.make(mv, &pieces)?;
+
+        self.replay.records.push(Record::Move {
+            mv,
+            hold,
+            lock,
+            search: Some(Search {
+                nodes: result.nodes,
+                depth: result.depth,
+                micros: elapsed.as_micros() as u64,
+            }),
+        });
+
+        self.stats.pieces += 1;
+        self.stats.attack += lock.sent as usize;
+        self.stats.cleared += lock.cleared as usize;
+
+        if lock.cleared > 0 {
+            let tspin = mv.tspin.is_some();
+            let quad = lock.cleared == 4 && !tspin;
+
+            self.stats.tspins += tspin as usize;
+            self.stats.quads += quad as usize;
+            self.stats.pcs += self.bot.root().board.is_empty() as usize;
+            self.stats.b2b = match tspin || quad {
+                true => self.stats.b2b + 1,
+                false => 0,
+            };
+            self.stats.max_b2b = self.stats.max_b2b.max(self.stats.b2b);
+            self.stats.max_combo = self.stats.max_combo.max(self.bot.root().combo as usize);
+        }
+
+        Ok((mv, lock))
+    }
+
+    // Change the board from outside the game, keeping the replay in step
+    pub fn reset(&mut self, board: Board, b2b: u8, combo: u8) {
+        self.bot
+            .reset(board, b2b, combo)
+            .expect("reset never fails");
+        self.replay
+            .records
+            .push(Record::Reset { board, b2b, combo });
+    }
+
+    // Cancel pending garbage with our attack, returning what is left to send
+    pub fn cancel(&mut self, mut sent: u8) -> u8 {
+        while sent > 0 {
+            let Some(front) = self.garbage.first_mut() else {
+                break;
+            };
+
+            let cancel = sent.min(front.lines);
+
+            front.lines -= cancel;
+            sent -= cancel;
+
+            if front.lines == 0 {
+                self.garbage.remove(0);
+            }
+        }
+
+        sent
+    }
+
+    pub fn tick(&mut self) {
+        for garbage in &mut self.garbage {
+            garbage.delay = garbage.delay.saturating_sub(1);
+        }
+    }
+
+    // Push every due attack onto the board, each line may move the hole with the given chance
+    pub fn land(&mut self, rng: &mut impl Rng, messiness: f64) -> usize {
+        let mut board = self.bot.root().board;
+        let mut landed = 0;
+
+        while let Some(garbage) = self.garbage.first().copied() {
+            if garbage.delay > 0 {
+                break;
+            }
+
+            let mut hole = rng.random_range(0..10);
+
+            for i in 0..garbage.lines {
+                if i > 0 && rng.random_bool(messiness) {
+                    hole = rng.random_range(0..10);
+                }
+
+                board.add_garbage(1, hole);
+            }
+
+            landed += garbage.lines as usize;
+
+            self.garbage.remove(0);
+        }
+
+        if landed > 0 {
+            let root = self.bot.root();
+            let (b2b, combo) = (root.b2b, root.combo);
+            let garbage = self.bot.garbage().saturating_add(landed as u8);
+
+            self.reset(board, b2b, combo);
+            self.bot.set_garbage(garbage);
+            self.stats.received += landed;
+        }
+
+        landed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{SeedableRng, rngs::StdRng};
+
+    use super::*;
+
+    const CONFIGS: BotConfigs = BotConfigs {
+        width: 16,
+        depth: 3,
+        branch: 0,
+        threads: 1,
+    };
+
+    #[test]
+    fn stats() {
+        let mut player = Player::new(Weights::default(), CONFIGS, 0, 5);
+
+        for _ in 0..50 {
+            player.play().unwrap();
+        }
+
+        let stats = player.stats;
+
+        // The totals add up to what the recorded moves locked as
+        let locks: Vec<_> = player
+            .replay
+            .records
+            .iter()
+            .filter_map(|record| match record {
+                Record::Move { lock, .. } => Some(lock),
+                _ => None,
+            })
+            .collect();
+
+        assert_eq!(stats.pieces, 50);
+        assert_eq!(locks.len(), 50);
+        assert_eq!(
+            stats.attack,
+            locks.iter().map(|lock| lock.sent as usize).sum::<usize>()
+        );
+        assert_eq!(
+            stats.cleared,
+            locks
+                .iter()
+                .map(|lock| lock.cleared as usize)
+                .sum::<usize>()
+        );
+        assert!(stats.nodes > 0);
+        assert!(stats.cleared > 0);
+        assert!(stats.tspins + stats.quads <= stats.cleared);
+        assert!(stats.b2b <= stats.max_b2b);
+        assert!(stats.max_combo > 0);
+
+        assert_eq!(stats.app(), stats.attack as f64 / 50.0);
+        assert_eq!(stats.lpp(), stats.cleared as f64 / 50.0);
+    }
+
+    #[test]
+    fn rates() {
+        let stats = Stats {
+            pieces: 40,
+            attack: 10,
+            cleared: 16,
+            ..Default::default()
+        };
+
+        assert_eq!(stats.app(), 0.25);
+        assert_eq!(stats.lpp(), 0.4);
+
+        // No pieces yet is no rate rather than a division by zero
+        assert_eq!(Stats::default().app(), 0.0);
+        assert_eq!(Stats::default().lpp(), 0.0);
+    }
+
+    #[test]
+    fn cancel() {
+        let mut player = Player::new(Weights::default(), CONFIGS, 0, 5);
+
+        player.garbage = vec![
+            Garbage { lines: 2, delay: 0 },
+            Garbage { lines: 3, delay: 1 },
+        ];
+
+        assert_eq!(player.cancel(3), 0);
+        assert_eq!(player.garbage, vec![Garbage { lines: 2, delay: 1 }]);
+        assert_eq!(player.cancel(4), 2);
+        assert!(player.garbage.is_empty());
+    }
+
+    #[test]
+    fn land() {
+        let mut player = Player::new(Weights::default(), CONFIGS, 0, 5);
+        let mut rng = StdRng::seed_from_u64(0);
+
+        player.garbage = vec![
+            Garbage { lines: 2, delay: 0 },
+            Garbage { lines: 3, delay: 1 },
+        ];
+
+        assert_eq!(player.land(&mut rng, 0.0), 2);
+
+        player.tick();
+
+        assert_eq!(player.land(&mut rng, 0.0), 3);
+        assert_eq!(player.bot.root().board.count(), 45);
+        assert_eq!(player.stats.received, 5);
+    }
+}
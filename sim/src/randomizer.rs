@@ -0,0 +1,32 @@
+use rand::{SeedableRng, rngs::StdRng, seq::SliceRandom};
+use strum::IntoEnumIterator;
+use tetris::piece::Piece;
+
+// Seeded 7-bag randomizer, yields pieces forever
+#[derive(Debug, Clone)]
+pub struct Randomizer {
+    rng: StdRng,
+    bag: Vec<Piece>,
+}
+
+impl Randomizer {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+            bag: Vec::with_capacity(7),
+        }
+    }
+}
+
+impl Iterator for Randomizer {
+    type Item = Piece;
+
+    fn next(&mut self) -> Option<Piece> {
+        if self.bag.is_empty() {
+            self.bag.extend(Piece::iter());
+            self.bag.shuffle(&mut self.rng);
+        }
+
+        self.bag.pop()
+    }
+}
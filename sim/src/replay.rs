@@ -0,0 +1,315 @@
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use tetris::{
+    board::Board,
+    movegen::movegen,
+    moves::Move,
+    piece::Piece,
+    state::{Lock, State},
+};
+
+use crate::randomizer::Randomizer;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Mode {
+    #[default]
+    Solo,
+    Versus,
+    Sprint,
+    Cheese,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Rules {
+    pub mode: Mode,
+    pub previews: usize,
+}
+
+// How the search behind a move went
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Search {
+    pub nodes: usize,
+    pub depth: usize,
+    pub micros: u64,
+}
+
+// One line of a replay file, the game header comes first and only there
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum Record {
+    Game {
+        rules: Rules,
+        seed: u64,
+        state: State,
+    },
+    Move {
+        mv: Move,
+        hold: bool,
+        lock: Lock,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        search: Option<Search>,
+    },
+    // The board changed outside of a move, garbage landing or a cheese race topping it up
+    Reset {
+        board: Board,
+        b2b: u8,
+        combo: u8,
+    },
+}
+
+// A whole game, the pieces come from the randomizer seeded with `seed` starting at `state`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Replay {
+    pub rules: Rules,
+    pub seed: u64,
+    pub state: State,
+    pub records: Vec<Record>,
+}
+
+// Walks through a replay record by record, pieces are dealt from the seed as they are needed
+#[derive(Debug, Clone)]
+pub struct Replayer {
+    randomizer: Randomizer,
+    queue: Vec<Piece>,
+    pub state: State,
+}
+
+// Lines are counted from 1 like an editor would, the header being line 1
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ReplayError {
+    #[error("line {0}: {1}")]
+    Parse(usize, String),
+    #[error("line {0}: expected the game header first and only there")]
+    Header(usize),
+    #[error("line {0}: {1:?} is neither the current piece nor the one to hold")]
+    Piece(usize, Piece),
+    #[error("line {0}: hold recorded as {1} but the move says otherwise")]
+    Hold(usize, bool),
+    #[error("line {0}: not a reachable placement")]
+    Illegal(usize),
+    #[error("line {line}: locked as {found:?} but recorded as {recorded:?}")]
+    Lock {
+        line: usize,
+        recorded: Lock,
+        found: Lock,
+    },
+}
+
+impl Replay {
+    pub fn new(rules: Rules, seed: u64, state: State) -> Self {
+        Self {
+            rules,
+            seed,
+            state,
+            records: Vec::new(),
+        }
+    }
+
+    pub fn moves(&self) -> usize {
+        self.records
+            .iter()
+            .filter(|record| matches!(record, Record::Move { .. }))
+            .count()
+    }
+
+    pub fn to_jsonl(&self) -> String {
+        let header = Record::Game {
+            rules: self.rules,
+            seed: self.seed,
+            state: self.state.clone(),
+        };
+
+        std::iter::once(&header)
+            .chain(&self.records)
+            .map(|record| serde_json::to_string(record).expect("records always serialize") + "\n")
+            .collect()
+    }
+
+    pub fn from_jsonl(jsonl: &str) -> Result<Self, ReplayError> {
+        let mut lines = jsonl
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(i, line)| {
+                serde_json::from_str(line)
+                    .map(|record| (i + 1, record))
+                    .map_err(|error| ReplayError::Parse(i + 1, error.to_string()))
+            });
+
+        let Some((_, Record::Game { rules, seed, state })) = lines.next().transpose()? else {
+            return Err(ReplayError::Header(1));
+        };
+
+        let mut replay = Replay::new(rules, seed, state);
+
+        for line in lines {
+            match line? {
+                (i, Record::Game { .. }) => return Err(ReplayError::Header(i)),
+                (_, record) => replay.records.push(record),
+            }
+        }
+
+        Ok(replay)
+    }
+
+    // Play every move again through `State::make`, checking it could be played at all and locks
+    // the way it was recorded, returning the state the game ended on
+    pub fn verify(&self) -> Result<State, ReplayError> {
+        let mut replayer = Replayer::new(self);
+
+        for (i, record) in self.records.iter().enumerate() {
+            replayer.step(i + 2, record)?;
+        }
+
+        Ok(replayer.state)
+    }
+}
+
+impl Replayer {
+    pub fn new(replay: &Replay) -> Self {
+        Self {
+            randomizer: Randomizer::new(replay.seed),
+            queue: Vec::new(),
+            state: replay.state.clone(),
+        }
+    }
+
+    // The current piece followed by as many as asked of the ones after it
+    pub fn queue(&mut self, next: usize) -> &[Piece] {
+        let missing = (self.state.next + next + 1).saturating_sub(self.queue.len());
+
+        self.queue.extend(self.randomizer.by_ref().take(missing));
+
+        &self.queue[self.state.next..=self.state.next + next]
+    }
+
+    // Apply the record found on the given line, the lock of a move played comes back
+    pub fn step(&mut self, line: usize, record: &Record) -> Result<Option<Lock>, ReplayError> {
+        match record {
+            Record::Game { .. } => Err(ReplayError::Header(line)),
+            Record::Reset { board, b2b, combo } => {
+                self.state.board = *board;
+                self.state.b2b = *b2b;
+                self.state.combo = *combo;
+
+                Ok(None)
+            }
+            Record::Move { mv, hold, lock, .. } => {
+                // Holding into an empty hold takes the piece after the current one
+                let (current, after) = (self.queue(1)[0], self.queue(1)[1]);
+                let state = &mut self.state;
+                let held = state.hold.unwrap_or(after);
+
+                if mv.kind != current && mv.kind != held {
+                    return Err(ReplayError::Piece(line, mv.kind));
+                }
+
+                if *hold != (mv.kind != current) {
+                    return Err(ReplayError::Hold(line, *hold));
+                }
+
+                if !movegen(&state.board, mv.kind).contains(mv) {
+                    return Err(ReplayError::Illegal(line));
+                }
+
+                let found = state.make(mv, &self.queue);
+
+                if found != *lock {
+                    return Err(ReplayError::Lock {
+                        line,
+                        recorded: *lock,
+                        found,
+                    });
+                }
+
+                Ok(Some(found))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{SeedableRng, rngs::StdRng};
+
+    use bot::{bot::BotConfigs, eval::Weights, garbage::Garbage};
+
+    use super::*;
+    use crate::player::Player;
+
+    const CONFIGS: BotConfigs = BotConfigs {
+        width: 16,
+        depth: 3,
+        branch: 0,
+        threads: 1,
+    };
+
+    // A short game with some garbage landing halfway through
+    fn game() -> Player {
+        let mut player = Player::new(Weights::default(), CONFIGS, 3, 5);
+        let mut rng = StdRng::seed_from_u64(0);
+
+        for i in 0..40 {
+            if i == 20 {
+                player.garbage.push(Garbage { lines: 3, delay: 0 });
+                player.land(&mut rng, 0.0);
+            }
+
+            player.play().unwrap();
+        }
+
+        player
+    }
+
+    #[test]
+    fn verify() {
+        let player = game();
+        let replay = Replay::from_jsonl(&player.replay.to_jsonl()).unwrap();
+
+        assert_eq!(replay, player.replay);
+        assert_eq!(replay.moves(), 40);
+        assert_eq!(replay.verify().unwrap().board, player.bot.root().board);
+    }
+
+    #[test]
+    fn diverge() {
+        let replay = game().replay;
+        let tamper = |edit: fn(&mut Move, &mut bool, &mut Lock)| {
+            let mut replay = replay.clone();
+
+            if let Some(Record::Move { mv, hold, lock, .. }) = replay.records.get_mut(4) {
+                edit(mv, hold, lock);
+            }
+
+            replay.verify()
+        };
+
+        assert_eq!(
+            tamper(|_, hold, _| *hold = !*hold),
+            Err(ReplayError::Hold(6, true))
+        );
+        assert_eq!(tamper(|mv, _, _| mv.y += 1), Err(ReplayError::Illegal(6)));
+        assert!(matches!(
+            tamper(|_, _, lock| lock.sent += 1),
+            Err(ReplayError::Lock { line: 6, .. })
+        ));
+    }
+
+    #[test]
+    fn parse() {
+        let jsonl = game().replay.to_jsonl();
+        let (header, rest) = jsonl.split_once('\n').unwrap();
+
+        assert_eq!(Replay::from_jsonl(rest), Err(ReplayError::Header(1)));
+        assert_eq!(
+            Replay::from_jsonl(&format!("{jsonl}{header}\n")),
+            Err(ReplayError::Header(42 + 1))
+        );
+        assert!(matches!(
+            Replay::from_jsonl(&format!("{header}\n{{\"type\":\"move\"}}\n")),
+            Err(ReplayError::Parse(2, _))
+        ));
+    }
+}
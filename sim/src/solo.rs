@@ -0,0 +1,189 @@
+use bot::{bot::BotConfigs, eval::Weights};
+
+use crate::{
+    player::{Player, Stats},
+    replay::Replay,
+};
+
+#[derive(Debug, Clone, Copy)]
+pub struct SoloConfigs {
+    pub previews: usize,
+    pub max_pieces: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct SoloResult {
+    pub dead: bool,
+    pub stats: Stats,
+    pub replay: Replay,
+}
+
+impl Default for SoloConfigs {
+    fn default() -> Self {
+        Self {
+            previews: 5,
+            max_pieces: 500,
+        }
+    }
+}
+
+// Play alone on a seeded queue until the piece limit or a top out
+pub fn solo(
+    weights: Weights,
+    bot_configs: BotConfigs,
+    seed: u64,
+    configs: &SoloConfigs,
+) -> SoloResult {
+    let mut player = Player::new(weights, bot_configs, seed, configs.previews);
+
+    while player.stats.pieces < configs.max_pieces {
+        if player.play().is_err() {
+            return SoloResult {
+                dead: true,
+                stats: player.stats,
+                replay: player.replay,
+            };
+        }
+    }
+
+    SoloResult {
+        dead: false,
+        stats: player.stats,
+        replay: player.replay,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bot::preset::Preset;
+    use tetris::{
+        board::Board,
+        state::{Lock, State},
+    };
+
+    use super::*;
+
+    const CONFIGS: BotConfigs = BotConfigs {
+        width: 16,
+        depth: 3,
+        branch: 0,
+        threads: 1,
+    };
+
+    // Play a seeded game from the given board, returning the player and every lock with the state
+    // it left behind
+    fn game(
+        weights: Weights,
+        seed: u64,
+        board: Board,
+        pieces: usize,
+    ) -> (Player, Vec<(Lock, State)>) {
+        let mut player = Player::new(weights, CONFIGS, seed, 5);
+        let mut history = Vec::new();
+
+        player.reset(board, 0, 0);
+
+        for _ in 0..pieces {
+            let (_, lock) = player.play().unwrap();
+
+            history.push((lock, player.bot.root().clone()));
+        }
+
+        (player, history)
+    }
+
+    fn max_height(history: &[(Lock, State)]) -> u32 {
+        history
+            .iter()
+            .map(|(_, state)| state.board.heights().into_iter().max().unwrap())
+            .max()
+            .unwrap()
+    }
+
+    #[test]
+    fn aggressive() {
+        let (aggressive, _) = game(Preset::Aggressive.weights(), 0, Board::new(), 100);
+        let (defensive, _) = game(Preset::Defensive.weights(), 0, Board::new(), 100);
+
+        assert!(aggressive.stats.attack > 2 * defensive.stats.attack);
+    }
+
+    #[test]
+    fn defensive() {
+        let (_, aggressive) = game(Preset::Aggressive.weights(), 0, Board::new(), 100);
+        let (_, defensive) = game(Preset::Defensive.weights(), 0, Board::new(), 100);
+
+        assert!(max_height(&defensive) <= 6);
+        assert!(max_height(&defensive) < max_height(&aggressive));
+    }
+
+    #[test]
+    fn combo() {
+        // A four wide well with a few cells already down, every piece after can clear a line
+        let mut board = Board::new();
+
+        for x in [0, 1, 2, 7, 8, 9] {
+            board.cols[x] = 0xff;
+        }
+
+        board.cols[3] = 0b11;
+        board.cols[4] = 0b1;
+
+        let combo = |weights| {
+            let (_, history) = game(weights, 0, board, 10);
+
+            history.iter().map(|(_, state)| state.combo).max().unwrap()
+        };
+
+        assert!(combo(Preset::Combo.weights()) >= 6);
+        assert!(combo(Preset::Combo.weights()) > combo(Weights::default()));
+    }
+
+    #[test]
+    fn pc() {
+        let clears = |weights| {
+            let (_, history) = game(weights, 2, Board::new(), 30);
+
+            history
+                .iter()
+                .filter(|(lock, state)| lock.cleared > 0 && state.board.is_empty())
+                .count()
+        };
+
+        assert!(clears(Preset::Pc.weights()) > 0);
+        assert_eq!(clears(Weights::default()), 0);
+    }
+
+    #[test]
+    fn sprint() {
+        let (sprint, _) = game(Preset::Sprint.weights(), 2, Board::new(), 100);
+        let (default, _) = game(Weights::default(), 2, Board::new(), 100);
+
+        // A hundred pieces fill at most forty lines
+        assert!(sprint.stats.cleared >= 38);
+        assert!(sprint.stats.cleared > default.stats.cleared);
+    }
+
+    #[test]
+    fn dig() {
+        // Eight lines of cheese, every line with its hole somewhere else
+        let mut board = Board::new();
+
+        for i in 0..8 {
+            board.add_garbage(1, (i * 3 + 1) % 10);
+        }
+
+        // Garbage lines left are the rows at the bottom with a single hole
+        let left = |weights| {
+            let (player, _) = game(weights, 1, board, 25);
+            let board = player.bot.root().board;
+
+            (0..8)
+                .take_while(|&y| board.cols.iter().filter(|col| *col >> y & 1 == 1).count() == 9)
+                .count()
+        };
+
+        assert_eq!(left(Preset::Dig.weights()), 0);
+        assert!(left(Weights::default()) > 0);
+    }
+}
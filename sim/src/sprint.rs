@@ -0,0 +1,109 @@
+use bot::{bot::BotConfigs, sprint::Sprint};
+use tetris::finesse::{finesse, inputs};
+
+use crate::{
+    player::Player,
+    replay::{Mode, Replay},
+};
+
+#[derive(Debug, Clone, Copy)]
+pub struct SprintConfigs {
+    pub lines: usize,
+    pub previews: usize,
+    pub max_pieces: usize,
+    // Presses per second the simulated player keeps up
+    pub kps: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct SprintResult {
+    pub finished: bool,
+    pub pieces: usize,
+    pub inputs: usize,
+    // Placements that took more presses than the same column and rotation on an open board
+    pub faults: usize,
+    // Seconds the presses take at the configured pace
+    pub time: f64,
+    pub replay: Replay,
+}
+
+impl Default for SprintConfigs {
+    fn default() -> Self {
+        Self {
+            lines: 40,
+            previews: 5,
+            max_pieces: 300,
+            kps: 10.0,
+        }
+    }
+}
+
+// Race to clear the lines on a seeded queue, every placement costs the fewest presses that get it
+// there, plus one for a hold
+pub fn sprint(bot_configs: BotConfigs, seed: u64, configs: &SprintConfigs) -> SprintResult {
+    let mut evaluator = Sprint::new(configs.lines as u32);
+
+    let mut player = Player::new(evaluator, bot_configs, seed, configs.previews);
+    let mut result = SprintResult {
+        finished: false,
+        pieces: 0,
+        inputs: 0,
+        faults: 0,
+        time: 0.0,
+        replay: Replay::default(),
+    };
+
+    player.replay.rules.mode = Mode::Sprint;
+
+    while player.stats.cleared < configs.lines && player.stats.pieces < configs.max_pieces {
+        let board = player.bot.root().board;
+        let current = player.bot.queue()[0];
+
+        let Ok((mv, lock)) = player.play() else {
+            break;
+        };
+
+        let presses = inputs(&board, &mv).unwrap_or_else(|| finesse(&mv));
+
+        result.inputs += presses as usize + (mv.kind != current) as usize;
+        result.faults += (presses > finesse(&mv)) as usize;
+
+        if lock.cleared > 0 {
+            evaluator.lines = configs.lines.saturating_sub(player.stats.cleared) as u32;
+            player.bot.set_evaluator(evaluator);
+        }
+    }
+
+    result.finished = player.stats.cleared >= configs.lines;
+    result.pieces = player.stats.pieces;
+    result.time = result.inputs as f64 / configs.kps;
+    result.replay = player.replay;
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sprint() {
+        let bot_configs = BotConfigs {
+            width: 16,
+            depth: 3,
+            branch: 0,
+            threads: 1,
+        };
+        let configs = SprintConfigs {
+            lines: 10,
+            ..Default::default()
+        };
+        let result = super::sprint(bot_configs, 0, &configs);
+
+        // Ten lines take at least a hundred cells, so 25 pieces, and each one at least a press
+        assert!(result.finished);
+        assert!(result.pieces >= 25);
+        assert!(result.inputs >= result.pieces);
+        assert_eq!(result.time, result.inputs as f64 / configs.kps);
+    }
+}
@@ -0,0 +1,151 @@
+// Sequential probability ratio test between two elo hypotheses, elo0 being "no better" and elo1
+// "better by this much", with the usual normal approximation of the game score
+#[derive(Debug, Clone, Copy)]
+pub struct Sprt {
+    pub elo0: f64,
+    pub elo1: f64,
+    pub alpha: f64,
+    pub beta: f64,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Tally {
+    pub wins: usize,
+    pub losses: usize,
+    pub draws: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    H0,
+    H1,
+}
+
+impl Default for Sprt {
+    fn default() -> Self {
+        Self {
+            elo0: 0.0,
+            elo1: 10.0,
+            alpha: 0.05,
+            beta: 0.05,
+        }
+    }
+}
+
+// Expected score against an opponent this many elo below
+fn score(elo: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf(-elo / 400.0))
+}
+
+fn elo(score: f64) -> f64 {
+    -400.0 * (1.0 / score - 1.0).log10()
+}
+
+impl Tally {
+    pub fn games(&self) -> usize {
+        self.wins + self.losses + self.draws
+    }
+
+    // Even before any game is in
+    pub fn score(&self) -> f64 {
+        match self.games() {
+            0 => 0.5,
+            games => (self.wins as f64 + self.draws as f64 / 2.0) / games as f64,
+        }
+    }
+
+    // Variance of a single game's score around the mean
+    fn variance(&self) -> f64 {
+        let s = self.score();
+        let n = self.games().max(1) as f64;
+
+        (self.wins as f64 * (1.0 - s).powi(2)
+            + self.draws as f64 * (0.5 - s).powi(2)
+            + self.losses as f64 * s.powi(2))
+            / n
+    }
+
+    // Elo difference and the half width of its 95% interval, clamped off the ends of the scale
+    pub fn elo(&self) -> (f64, f64) {
+        let clamp = |s: f64| s.clamp(0.001, 0.999);
+        let s = self.score();
+        let margin = 1.96 * (self.variance() / self.games().max(1) as f64).sqrt();
+
+        (
+            elo(clamp(s)),
+            (elo(clamp(s + margin)) - elo(clamp(s - margin))) / 2.0,
+        )
+    }
+}
+
+impl Sprt {
+    // Lower and upper stopping bounds of the log likelihood ratio
+    pub fn bounds(&self) -> (f64, f64) {
+        (
+            (self.beta / (1.0 - self.alpha)).ln(),
+            ((1.0 - self.beta) / self.alpha).ln(),
+        )
+    }
+
+    pub fn llr(&self, tally: &Tally) -> f64 {
+        let variance = tally.variance();
+
+        if variance == 0.0 {
+            return 0.0;
+        }
+
+        let (s0, s1) = (score(self.elo0), score(self.elo1));
+        let total = tally.wins as f64 + tally.draws as f64 / 2.0;
+
+        (s1 - s0) * (total - tally.games() as f64 * (s0 + s1) / 2.0) / variance
+    }
+
+    pub fn verdict(&self, tally: &Tally) -> Option<Verdict> {
+        let llr = self.llr(tally);
+        let (lower, upper) = self.bounds();
+
+        match llr {
+            _ if llr <= lower => Some(Verdict::H0),
+            _ if llr >= upper => Some(Verdict::H1),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn elo() {
+        let tally = Tally {
+            wins: 30,
+            losses: 10,
+            draws: 0,
+        };
+        let (elo, margin) = tally.elo();
+
+        // A 75% score is a little over 190 elo
+        assert!((elo - 190.85).abs() < 0.01);
+        assert!(margin > 0.0);
+        assert_eq!(Tally::default().elo().0, 0.0);
+    }
+
+    #[test]
+    fn verdict() {
+        let sprt = Sprt::default();
+        let tally = |wins, losses| Tally {
+            wins,
+            losses,
+            draws: 10,
+        };
+
+        assert_eq!(sprt.llr(&Tally::default()), 0.0);
+        assert_eq!(sprt.verdict(&tally(12, 10)), None);
+        assert_eq!(sprt.verdict(&tally(600, 400)), Some(Verdict::H1));
+        assert_eq!(sprt.verdict(&tally(400, 600)), Some(Verdict::H0));
+
+        // Dead even sits between the hypotheses, so it takes longer to give up on it
+        assert!(sprt.llr(&tally(500, 500)) < 0.0);
+    }
+}
@@ -0,0 +1,86 @@
+use rand::{SeedableRng, rngs::StdRng};
+
+use bot::{bot::BotConfigs, eval::Weights, garbage::Garbage};
+
+use crate::{
+    player::{Player, Stats},
+    replay::{Mode, Replay},
+};
+
+#[derive(Debug, Clone, Copy)]
+pub struct VersusConfigs {
+    pub delay: usize,
+    pub messiness: f64,
+    pub previews: usize,
+    pub max_pieces: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct VersusResult {
+    pub winner: Option<usize>,
+    pub stats: [Stats; 2],
+    pub replays: [Replay; 2],
+}
+
+impl Default for VersusConfigs {
+    fn default() -> Self {
+        Self {
+            delay: 1,
+            messiness: 0.1,
+            previews: 5,
+            max_pieces: 500,
+        }
+    }
+}
+
+// Play both bots on the same seeded queue, taking turns piece by piece until someone tops out,
+// moving first is an edge so odd seeds hand it to the second bot
+pub fn versus(
+    bots: [(Weights, BotConfigs); 2],
+    seed: u64,
+    configs: &VersusConfigs,
+) -> VersusResult {
+    let mut players = bots
+        .map(|(weights, bot_configs)| Player::new(weights, bot_configs, seed, configs.previews));
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut winner = None;
+
+    for player in &mut players {
+        player.replay.rules.mode = Mode::Versus;
+    }
+
+    let first = (seed % 2) as usize;
+
+    'game: for _ in 0..configs.max_pieces {
+        for turn in 0..2 {
+            let i = (first + turn) % 2;
+            let player = &mut players[i];
+
+            let Ok((_, lock)) = player.play() else {
+                winner = Some(1 - i);
+                break 'game;
+            };
+
+            let sent = player.cancel(lock.sent);
+
+            player.tick();
+
+            if lock.cleared == 0 {
+                player.land(&mut rng, configs.messiness);
+            }
+
+            if sent > 0 {
+                players[1 - i].garbage.push(Garbage {
+                    lines: sent,
+                    delay: configs.delay,
+                });
+            }
+        }
+    }
+
+    VersusResult {
+        winner,
+        stats: players.each_ref().map(|player| player.stats),
+        replays: players.map(|player| player.replay),
+    }
+}
@@ -0,0 +1,51 @@
+use std::str::FromStr;
+
+use bot::{bot::BotError, eval::Weights, preset::Preset};
+
+fn fail(message: String) -> ! {
+    eprintln!("{message}");
+    std::process::exit(1);
+}
+
+// Value following a `--name` flag
+pub fn value<'a>(args: &'a [String], name: &str) -> Option<&'a str> {
+    args.iter()
+        .position(|arg| arg == name)
+        .map(|i| match args.get(i + 1) {
+            Some(value) => value.as_str(),
+            None => fail(format!("missing value for {name}")),
+        })
+}
+
+pub fn optional<T: FromStr>(args: &[String], name: &str) -> Option<T> {
+    value(args, name).map(|value| {
+        value
+            .parse()
+            .unwrap_or_else(|_| fail(format!("invalid value for {name}: {value}")))
+    })
+}
+
+pub fn parse<T: FromStr>(args: &[String], name: &str, default: T) -> T {
+    optional(args, name).unwrap_or(default)
+}
+
+// Weights loaded from the file given after the flag, else those of the named preset, the defaults
+// otherwise
+pub fn weights(args: &[String], name: &str, preset: &str) -> Weights {
+    load(args, name, Weights::from_json).unwrap_or_else(|| {
+        optional::<Preset>(args, preset).map_or_else(Weights::default, Preset::weights)
+    })
+}
+
+// Whatever `parse` makes of the file given after the flag, if any
+pub fn load<T>(
+    args: &[String],
+    name: &str,
+    parse: impl Fn(&str) -> Result<T, BotError>,
+) -> Option<T> {
+    let path = value(args, name)?;
+    let json = std::fs::read_to_string(path)
+        .unwrap_or_else(|error| fail(format!("failed to read {path}: {error}")));
+
+    Some(parse(&json).unwrap_or_else(|error| fail(format!("{path}: {error}"))))
+}
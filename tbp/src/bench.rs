@@ -77,20 +77,16 @@ pub fn bench() {
             Piece::S,
         ];
 
-        let bot = BotState::new(
+        let mut bot = BotState::new(
             State {
-                board: board,
+                board,
                 hold: None,
                 bag: Bag::all(),
                 next: 0,
                 b2b: 0,
                 combo: 0,
             },
-            Lock {
-                cleared: 0,
-                sent: 0,
-                softdrop: false,
-            },
+            Lock::default(),
             queue,
             Weights::default(),
         )
@@ -102,7 +98,8 @@ pub fn bench() {
             .search(BotConfigs {
                 width: 250,
                 depth: 12,
-                branch: 1,
+                branch: 0,
+                threads: 1,
             })
             .expect("bot dead!")
             .nodes;
@@ -0,0 +1,59 @@
+use bot::{bot::BotConfigs, dig::Dig};
+use sim::{
+    cheese::{CheeseConfigs, cheese as play},
+    parallel,
+};
+
+use crate::{args, replay};
+
+pub fn cheese(args: &[String]) {
+    let games: u64 = args::parse(args, "--games", 10);
+    let seed: u64 = args::parse(args, "--seed", 0);
+    let threads: usize = args::parse(args, "--threads", 1);
+
+    let configs = BotConfigs {
+        width: args::parse(args, "--width", 250),
+        depth: args::parse(args, "--depth", 12),
+        branch: 0,
+        threads: 1,
+    };
+    let cheese_configs = CheeseConfigs {
+        lines: args::parse(args, "--lines", 100),
+        rows: args::parse(args, "--rows", 10),
+        change: args::parse(args, "--change", 1.0),
+        messiness: args::parse(args, "--messiness", 0.0),
+        ..CheeseConfigs::default()
+    };
+
+    let results = parallel::map(games as usize, threads, |i| {
+        let result = play(Dig::default(), configs, seed + i as u64, &cheese_configs);
+
+        replay::save(args, &format!("cheese-{}", seed + i as u64), &result.replay);
+
+        result
+    });
+
+    for (i, result) in results.iter().enumerate() {
+        println!(
+            "game {}: {} - pieces {} - garbage {} - pieces per line {:.3}",
+            seed + i as u64,
+            match result.finished {
+                true => "finished",
+                false => "failed",
+            },
+            result.pieces,
+            result.garbage,
+            result.pieces_per_line(),
+        );
+    }
+
+    let pieces: usize = results.iter().map(|result| result.pieces).sum();
+    let garbage: usize = results.iter().map(|result| result.garbage).sum();
+
+    println!(
+        "finished {}/{} - pieces per line {:.3}",
+        results.iter().filter(|result| result.finished).count(),
+        results.len(),
+        pieces as f64 / garbage.max(1) as f64,
+    );
+}
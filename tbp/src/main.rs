@@ -1,18 +1,41 @@
+use std::time::{Duration, Instant};
+
 use bot::{
-    bot::{BotConfigs, BotState, best_move},
-    eval::Weights,
+    blend::Blend,
+    book::Book,
+    bot::{BotConfigs, BotState, Budget, Limit, best_move},
+    eval::Evaluator,
+    model::Model,
 };
 use rand::{rng, seq::SliceRandom};
 use tetris::{
     bag::Bag,
     board::Board,
+    colored::ColoredBoard,
     piece::Piece,
     state::{Lock, State},
 };
 
-use crate::bench::bench;
+use crate::{
+    bench::bench,
+    cheese::cheese,
+    replay::{verify, view},
+    run::run,
+    sprint::sprint,
+    sprt::sprt,
+    ui::{Counters, Frame},
+    versus::versus,
+};
 
+mod args;
 mod bench;
+mod cheese;
+mod replay;
+mod run;
+mod sprint;
+mod sprt;
+mod ui;
+mod versus;
 
 fn random_queue(bag: usize) -> Vec<Piece> {
     let mut queue = Vec::new();
@@ -41,25 +64,49 @@ fn random_queue(bag: usize) -> Vec<Piece> {
 fn main() {
     let args: Vec<String> = std::env::args().collect();
 
-    match args.len() {
-        2 => match &args[1][..] {
-            "bench" => {
-                bench();
-                return;
-            }
-            _ => {}
-        },
+    match args.get(1).map(String::as_str) {
+        Some("bench") => return bench(),
+        Some("versus") => return versus(&args[2..]),
+        Some("sprint") => return sprint(&args[2..]),
+        Some("cheese") => return cheese(&args[2..]),
+        Some("run") => return run(&args[2..]),
+        Some("sprt") => return sprt(&args[2..]),
+        Some("verify") => return verify(&args[2..]),
+        Some("view") => return view(&args[2..]),
         _ => {}
     }
 
-    let weights = Weights::default();
+    let time: Option<u64> = args::optional(&args, "--time");
+    let explain = args.iter().any(|arg| arg == "--explain");
+    let book = args::load(&args, "--book", Book::from_json);
 
     let configs = BotConfigs {
         width: 250,
         depth: 12,
-        branch: 1,
+        branch: 0,
+        threads: args::parse(&args, "--threads", 1),
     };
 
+    if let Some(model) = args::load(&args, "--model", Model::from_json) {
+        return play(model, configs, book, time, explain);
+    }
+
+    if let Some(blend) = args::load(&args, "--blend", Blend::from_json) {
+        return play(blend, configs, book, time, explain);
+    }
+
+    let weights = args::weights(&args, "--weights", "--preset");
+
+    play(weights, configs, book, time, explain);
+}
+
+fn play<E: Evaluator>(
+    evaluator: E,
+    configs: BotConfigs,
+    book: Option<Book>,
+    time: Option<u64>,
+    explain: bool,
+) {
     let mut queue = random_queue(1000);
 
     let mut bot = BotState::new(
@@ -71,21 +118,34 @@ fn main() {
             b2b: 0,
             combo: 0,
         },
-        Lock {
-            cleared: 0,
-            sent: 0,
-            softdrop: false,
-        },
+        Lock::default(),
         queue.drain(..12).collect(),
-        weights,
+        evaluator,
     )
     .expect("bot should be valid smh!");
 
+    if let Some(book) = book {
+        bot.set_book(book);
+    }
+
     let mut holded = false;
+    let mut colored = ColoredBoard::new();
+    let mut counters = Counters::default();
 
     for _ in 0..1000 {
-        if let Ok(result) = bot.search(configs) {
-            let mv = match best_move(&result, 0) {
+        let result = match time {
+            Some(ms) => bot.search_within(
+                configs,
+                Budget {
+                    limit: Limit::Deadline(Instant::now() + Duration::from_millis(ms)),
+                    adaptive: true,
+                },
+            ),
+            None => bot.search(configs),
+        };
+
+        if let Ok(result) = result {
+            let mv = match best_move(&result) {
                 Ok(mv) => mv,
                 _ => {
                     println!("death!");
@@ -93,6 +153,43 @@ fn main() {
                 }
             };
 
+            ui::draw(&Frame {
+                board: &colored,
+                hold: bot.root().hold,
+                next: &bot.queue()[1..],
+                ghost: Some(mv),
+                counters,
+            });
+
+            println!("nodes: {}", result.nodes);
+            println!("depth: {}", result.depth);
+
+            // Pieces the bot plans to place next, held ones are starred and guessed ones past the
+            // queue get a question mark
+            if let Some(best) = result.candidates.iter().max_by_key(|c| c.score) {
+                let plan: Vec<_> = best
+                    .line
+                    .iter()
+                    .map(|step| {
+                        let hold = if step.hold { "*" } else { "" };
+                        let guess = if step.chance.is_some() { "?" } else { "" };
+
+                        format!("{hold}{:?}{guess}", step.mv.kind)
+                    })
+                    .collect();
+
+                println!("plan: {}", plan.join(" "));
+
+                if explain {
+                    for term in &best.terms {
+                        println!(
+                            "{:>12}: {:>4} -> {:>6}",
+                            term.name, term.feature, term.score
+                        );
+                    }
+                }
+            }
+
             let mut nexts = Vec::new();
 
             if mv.kind == *queue.first().unwrap() && !holded {
@@ -102,16 +199,16 @@ fn main() {
 
             nexts.push(queue.remove(0));
 
-            if bot.make(mv, &nexts).is_err() {
+            let Ok(lock) = bot.make(mv, &nexts) else {
                 println!("invalid nexts!");
                 break;
-            }
+            };
 
-            println!("{}", bot.root().board);
-            println!("nodes: {}", result.nodes);
-            println!("depth: {}", result.depth);
+            colored.place(&mv);
+            colored.clear_lines();
+            counters.update(&lock, bot.root());
 
-            std::thread::sleep(std::time::Duration::from_millis(200));
+            std::thread::sleep(Duration::from_millis(200));
         } else {
             println!("death!");
             break;
@@ -0,0 +1,134 @@
+use std::{path::Path, time::Duration};
+
+use sim::replay::{Record, Replay, Replayer};
+use tetris::colored::ColoredBoard;
+
+use crate::{
+    args,
+    ui::{self, Counters, Frame},
+};
+
+// Write a game into the directory given after --replays, if any, as `name.jsonl`
+pub fn save(args: &[String], name: &str, replay: &Replay) {
+    let Some(dir) = args::value(args, "--replays") else {
+        return;
+    };
+
+    let path = Path::new(dir).join(format!("{name}.jsonl"));
+
+    if let Err(error) =
+        std::fs::create_dir_all(dir).and_then(|_| std::fs::write(&path, replay.to_jsonl()))
+    {
+        eprintln!("failed to write {}: {error}", path.display());
+        std::process::exit(1);
+    }
+}
+
+// Play every given replay file again, failing if any of them diverges from what it recorded
+pub fn verify(args: &[String]) {
+    let mut failed = false;
+
+    for path in args {
+        let result = std::fs::read_to_string(path)
+            .map_err(|error| error.to_string())
+            .and_then(|jsonl| {
+                let replay = Replay::from_jsonl(&jsonl).map_err(|error| error.to_string())?;
+
+                replay.verify().map_err(|error| error.to_string())?;
+
+                Ok(replay.moves())
+            });
+
+        match result {
+            Ok(moves) => println!("{path}: ok - {moves} moves"),
+            Err(error) => {
+                println!("{path}: {error}");
+                failed = true;
+            }
+        }
+    }
+
+    if failed {
+        std::process::exit(1);
+    }
+}
+
+// Step through a replay file a placement at a time, on Enter or every --delay milliseconds
+pub fn view(args: &[String]) {
+    let Some(path) = args.first() else {
+        eprintln!("usage: tbp view <replay> [--delay ms]");
+        std::process::exit(1);
+    };
+    let delay: Option<u64> = args::optional(args, "--delay");
+
+    let replay = std::fs::read_to_string(path)
+        .map_err(|error| error.to_string())
+        .and_then(|jsonl| Replay::from_jsonl(&jsonl).map_err(|error| error.to_string()))
+        .unwrap_or_else(|error| {
+            eprintln!("{path}: {error}");
+            std::process::exit(1);
+        });
+
+    let mut replayer = Replayer::new(&replay);
+    let mut board = ColoredBoard::from(replay.state.board);
+    let mut counters = Counters::default();
+
+    counters.sync(&replay.state);
+
+    for (i, record) in replay.records.iter().enumerate() {
+        if let Record::Move { mv, search, .. } = record {
+            let queue = replayer.queue(replay.rules.previews).to_vec();
+
+            ui::draw(&Frame {
+                board: &board,
+                hold: replayer.state.hold,
+                next: &queue[1..],
+                ghost: Some(*mv),
+                counters,
+            });
+
+            if let Some(search) = search {
+                println!(
+                    "nodes {} - depth {} - {:.1}ms",
+                    search.nodes,
+                    search.depth,
+                    search.micros as f64 / 1000.0
+                );
+            }
+
+            match delay {
+                Some(ms) => std::thread::sleep(Duration::from_millis(ms)),
+                None => {
+                    let _ = std::io::stdin().read_line(&mut String::new());
+                }
+            }
+        }
+
+        let lock = replayer.step(i + 2, record).unwrap_or_else(|error| {
+            eprintln!("{path}: {error}");
+            std::process::exit(1);
+        });
+
+        match (record, lock) {
+            (Record::Move { mv, .. }, Some(lock)) => {
+                board.place(mv);
+                board.clear_lines();
+                counters.update(&lock, &replayer.state);
+            }
+            _ => {
+                board.sync(&replayer.state.board);
+                counters.sync(&replayer.state);
+            }
+        }
+    }
+
+    let queue = replayer.queue(replay.rules.previews).to_vec();
+
+    ui::draw(&Frame {
+        board: &board,
+        hold: replayer.state.hold,
+        next: &queue,
+        ghost: None,
+        counters,
+    });
+}
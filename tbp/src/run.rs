@@ -0,0 +1,127 @@
+use serde::Serialize;
+
+use bot::bot::BotConfigs;
+use sim::{
+    parallel,
+    solo::{SoloConfigs, SoloResult, solo},
+};
+
+use crate::{args, replay};
+
+// Totals over every game and the rates that matter, per piece or per move
+#[derive(Debug, Clone, Default, Serialize)]
+struct Report {
+    games: usize,
+    deaths: usize,
+    pieces: usize,
+    attack: usize,
+    lines: usize,
+    tspins: usize,
+    quads: usize,
+    pcs: usize,
+    max_b2b: usize,
+    max_combo: usize,
+    app: f64,
+    lpp: f64,
+    death_rate: f64,
+    nodes_per_move: f64,
+    ms_per_move: f64,
+}
+
+impl Report {
+    fn new(results: &[SoloResult]) -> Self {
+        let mut report = Report {
+            games: results.len(),
+            ..Default::default()
+        };
+        let mut nodes = 0;
+        let mut time = 0.0;
+
+        for SoloResult { dead, stats, .. } in results {
+            report.deaths += *dead as usize;
+            report.pieces += stats.pieces;
+            report.attack += stats.attack;
+            report.lines += stats.cleared;
+            report.tspins += stats.tspins;
+            report.quads += stats.quads;
+            report.pcs += stats.pcs;
+            report.max_b2b = report.max_b2b.max(stats.max_b2b);
+            report.max_combo = report.max_combo.max(stats.max_combo);
+            nodes += stats.nodes;
+            time += stats.time.as_secs_f64() * 1000.0;
+        }
+
+        let moves = report.pieces.max(1) as f64;
+
+        report.app = report.attack as f64 / moves;
+        report.lpp = report.lines as f64 / moves;
+        report.death_rate = report.deaths as f64 / report.games.max(1) as f64;
+        report.nodes_per_move = nodes as f64 / moves;
+        report.ms_per_move = time / moves;
+
+        report
+    }
+
+    fn table(&self) -> String {
+        let rows = [
+            ("games", self.games.to_string()),
+            ("deaths", self.deaths.to_string()),
+            ("death rate", format!("{:.3}", self.death_rate)),
+            ("pieces", self.pieces.to_string()),
+            ("attack", self.attack.to_string()),
+            ("app", format!("{:.3}", self.app)),
+            ("lines", self.lines.to_string()),
+            ("lpp", format!("{:.3}", self.lpp)),
+            ("tspins", self.tspins.to_string()),
+            ("quads", self.quads.to_string()),
+            ("pcs", self.pcs.to_string()),
+            ("max b2b", self.max_b2b.to_string()),
+            ("max combo", self.max_combo.to_string()),
+            ("nodes/move", format!("{:.0}", self.nodes_per_move)),
+            ("ms/move", format!("{:.2}", self.ms_per_move)),
+        ];
+
+        rows.iter()
+            .map(|(name, value)| format!("{name:<12}{value:>12}\n"))
+            .collect()
+    }
+}
+
+// Play seeded solo games without drawing anything and report on all of them
+pub fn run(args: &[String]) {
+    let games: usize = args::parse(args, "--games", 10);
+    let seed: u64 = args::parse(args, "--seed", 0);
+    let threads: usize = args::parse(args, "--threads", 1);
+    let weights = args::weights(args, "--weights", "--preset");
+
+    let configs = BotConfigs {
+        width: args::parse(args, "--width", 250),
+        depth: args::parse(args, "--depth", 12),
+        branch: args::parse(args, "--branch", 0),
+        threads: 1,
+    };
+    let solo_configs = SoloConfigs {
+        max_pieces: args::parse(args, "--pieces", 500),
+        ..SoloConfigs::default()
+    };
+
+    let results = parallel::map(games, threads, |i| {
+        let result = solo(weights, configs, seed + i as u64, &solo_configs);
+
+        replay::save(args, &format!("solo-{}", seed + i as u64), &result.replay);
+
+        result
+    });
+    let report = Report::new(&results);
+
+    print!("{}", report.table());
+
+    if let Some(path) = args::value(args, "--json") {
+        let json = serde_json::to_string_pretty(&report).expect("report always serializes");
+
+        if let Err(error) = std::fs::write(path, json) {
+            eprintln!("failed to write {path}: {error}");
+            std::process::exit(1);
+        }
+    }
+}
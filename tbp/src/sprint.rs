@@ -0,0 +1,63 @@
+use bot::bot::BotConfigs;
+use sim::{
+    parallel,
+    sprint::{SprintConfigs, SprintResult, sprint as play},
+};
+
+use crate::{args, replay};
+
+pub fn sprint(args: &[String]) {
+    let games: u64 = args::parse(args, "--games", 10);
+    let seed: u64 = args::parse(args, "--seed", 0);
+    let threads: usize = args::parse(args, "--threads", 1);
+
+    let configs = BotConfigs {
+        width: args::parse(args, "--width", 250),
+        depth: args::parse(args, "--depth", 12),
+        branch: 0,
+        threads: 1,
+    };
+    let sprint_configs = SprintConfigs {
+        lines: args::parse(args, "--lines", 40),
+        kps: args::parse(args, "--kps", 10.0),
+        ..SprintConfigs::default()
+    };
+
+    let results = parallel::map(games as usize, threads, |i| {
+        let result = play(configs, seed + i as u64, &sprint_configs);
+
+        replay::save(args, &format!("sprint-{}", seed + i as u64), &result.replay);
+
+        result
+    });
+
+    for (i, result) in results.iter().enumerate() {
+        println!(
+            "game {}: {} - pieces {} - inputs {} - faults {} - time {:.2}s",
+            seed + i as u64,
+            match result.finished {
+                true => "finished",
+                false => "failed",
+            },
+            result.pieces,
+            result.inputs,
+            result.faults,
+            result.time,
+        );
+    }
+
+    let finished: Vec<_> = results.iter().filter(|result| result.finished).collect();
+    let mean = |stat: fn(&&SprintResult) -> f64| {
+        finished.iter().map(stat).sum::<f64>() / finished.len().max(1) as f64
+    };
+
+    println!(
+        "finished {}/{} - pieces {:.1} - inputs {:.1} - faults {:.1} - time {:.2}s",
+        finished.len(),
+        results.len(),
+        mean(|result| result.pieces as f64),
+        mean(|result| result.inputs as f64),
+        mean(|result| result.faults as f64),
+        mean(|result| result.time),
+    );
+}
@@ -0,0 +1,100 @@
+use bot::bot::BotConfigs;
+use sim::{
+    parallel,
+    sprt::{Sprt, Tally, Verdict},
+    versus::{VersusConfigs, versus},
+};
+
+use crate::{args, replay};
+
+// Play pairs of games on the same seed with sides swapped, the first bot is the one under test,
+// until the test concludes or the games run out
+pub fn sprt(args: &[String]) {
+    let games: usize = args::parse(args, "--games", 1000);
+    let seed: u64 = args::parse(args, "--seed", 0);
+    let threads: usize = args::parse(args, "--threads", 1);
+
+    let sprt = Sprt {
+        elo0: args::parse(args, "--elo0", 0.0),
+        elo1: args::parse(args, "--elo1", 10.0),
+        alpha: args::parse(args, "--alpha", 0.05),
+        beta: args::parse(args, "--beta", 0.05),
+    };
+    let configs = |width, depth| BotConfigs {
+        width: args::parse(args, width, 250),
+        depth: args::parse(args, depth, 12),
+        branch: 0,
+        threads: 1,
+    };
+    let new = (
+        args::weights(args, "--weights", "--preset"),
+        configs("--width", "--depth"),
+    );
+    let base = (
+        args::weights(args, "--against", "--against-preset"),
+        configs("--against-width", "--against-depth"),
+    );
+    let versus_configs = VersusConfigs::default();
+
+    let (lower, upper) = sprt.bounds();
+    let mut tally = Tally::default();
+    let mut pair = 0;
+
+    while tally.games() < games {
+        let batch = threads.max(1).min((games - tally.games()).div_ceil(2));
+        let results = parallel::map(batch, threads, |i| {
+            let seed = seed + (pair + i) as u64;
+            let games = [
+                versus([new, base], seed, &versus_configs),
+                versus([base, new], seed, &versus_configs),
+            ];
+
+            for (game, result) in games.iter().enumerate() {
+                for (player, replay) in result.replays.iter().enumerate() {
+                    replay::save(args, &format!("sprt-{seed}-{game}-{player}"), replay);
+                }
+            }
+
+            [games[0].winner, games[1].winner.map(|w| 1 - w)]
+        });
+
+        for winners in results {
+            for winner in winners {
+                match winner {
+                    Some(0) => tally.wins += 1,
+                    Some(_) => tally.losses += 1,
+                    None => tally.draws += 1,
+                }
+            }
+
+            let (elo, margin) = tally.elo();
+
+            println!(
+                "pair {}: {}-{}-{} - elo {elo:.1} +/- {margin:.1} - llr {:.2} ({lower:.2}, {upper:.2})",
+                seed + pair as u64,
+                tally.wins,
+                tally.losses,
+                tally.draws,
+                sprt.llr(&tally),
+            );
+
+            pair += 1;
+
+            if let Some(verdict) = sprt.verdict(&tally) {
+                let (name, elo, other) = match verdict {
+                    Verdict::H1 => ("H1", sprt.elo1, sprt.elo0),
+                    Verdict::H0 => ("H0", sprt.elo0, sprt.elo1),
+                };
+
+                println!(
+                    "{name} accepted after {} games: {elo} elo is more likely than {other}",
+                    tally.games(),
+                );
+
+                return;
+            }
+        }
+    }
+
+    println!("inconclusive after {} games", tally.games());
+}
@@ -0,0 +1,164 @@
+use tetris::{
+    colored::{Cell, ColoredBoard},
+    moves::Move,
+    piece::{Piece, Rotation},
+    state::{Lock, State},
+};
+
+const ROWS: usize = 20;
+
+// Running totals shown next to the board, b2b counts the chain rather than the capped state
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Counters {
+    pub pieces: usize,
+    pub lines: usize,
+    pub attack: usize,
+    pub b2b: usize,
+    pub combo: usize,
+}
+
+// Everything drawn in one go, the current piece is shown through the ghost of its placement
+#[derive(Debug, Clone, Copy)]
+pub struct Frame<'a> {
+    pub board: &'a ColoredBoard,
+    pub hold: Option<Piece>,
+    pub next: &'a [Piece],
+    pub ghost: Option<Move>,
+    pub counters: Counters,
+}
+
+impl Counters {
+    // Count a move from how it locked and the state it left, a clear that kept the engine's b2b
+    // going adds to the chain
+    pub fn update(&mut self, lock: &Lock, state: &State) {
+        self.pieces += 1;
+        self.lines += lock.cleared as usize;
+        self.attack += lock.sent as usize;
+
+        if lock.cleared > 0 {
+            self.b2b = match state.b2b > 0 {
+                true => self.b2b + 1,
+                false => 0,
+            };
+        }
+
+        self.combo = state.combo as usize;
+    }
+
+    // Catch up with a state changed outside of a move, which can break the chain but not say how
+    // long it was
+    pub fn sync(&mut self, state: &State) {
+        self.b2b = match state.b2b {
+            0 => 0,
+            b2b => self.b2b.max(b2b as usize),
+        };
+        self.combo = state.combo as usize;
+    }
+}
+
+// Guideline colors out of the 256 color palette
+fn color(cell: Cell) -> u8 {
+    match cell {
+        Cell::Piece(Piece::I) => 51,
+        Cell::Piece(Piece::O) => 226,
+        Cell::Piece(Piece::T) => 129,
+        Cell::Piece(Piece::S) => 46,
+        Cell::Piece(Piece::Z) => 196,
+        Cell::Piece(Piece::J) => 21,
+        Cell::Piece(Piece::L) => 208,
+        Cell::Garbage => 244,
+    }
+}
+
+// Every cell is two columns wide so the board comes out roughly square
+fn paint(cell: Option<Cell>) -> String {
+    match cell {
+        Some(cell) => format!("\x1b[48;5;{}m  \x1b[0m", color(cell)),
+        None => "\x1b[90m .\x1b[0m".to_string(),
+    }
+}
+
+fn ghost(kind: Piece) -> String {
+    format!("\x1b[38;5;{}m[]\x1b[0m", color(Cell::Piece(kind)))
+}
+
+// A piece in its spawn rotation, two rows of four cells
+fn preview(piece: Option<Piece>) -> [String; 2] {
+    let cells = piece.map(|piece| piece.cells(Rotation::North));
+
+    [1, 0].map(|y| {
+        (-1..3)
+            .map(
+                |x| match cells.is_some_and(|cells| cells.contains(&(x, y))) {
+                    true => paint(piece.map(Cell::Piece)),
+                    false => "  ".to_string(),
+                },
+            )
+            .collect()
+    })
+}
+
+pub fn render(frame: &Frame) -> String {
+    // Side panels line up with the top border and the board rows under it
+    let mut left = vec![String::new(); ROWS + 2];
+    let mut right = vec![String::new(); ROWS + 2];
+
+    left[0] = "HOLD    ".to_string();
+    left[1..3].clone_from_slice(&preview(frame.hold));
+
+    right[0] = "NEXT".to_string();
+
+    for (i, &piece) in frame.next.iter().take(5).enumerate() {
+        right[1 + i * 3..3 + i * 3].clone_from_slice(&preview(Some(piece)));
+    }
+
+    let counters = &frame.counters;
+    let lines = [
+        format!("b2b    {}", counters.b2b),
+        format!("combo  {}", counters.combo),
+        format!("attack {}", counters.attack),
+        format!("lines  {}", counters.lines),
+        format!("pieces {}", counters.pieces),
+    ];
+
+    let start = right.len() - lines.len();
+
+    right[start..].clone_from_slice(&lines);
+
+    let border = format!("+{}+", "-".repeat(20));
+    let mut out = String::new();
+
+    for (i, (left, right)) in left.iter().zip(&right).enumerate() {
+        let middle = match i {
+            _ if i == 0 || i > ROWS => border.clone(),
+            _ => {
+                let y = (ROWS - i) as i8;
+                let row: String = (0..10)
+                    .map(
+                        |x| match (frame.board.get(x as usize, y as usize), frame.ghost) {
+                            (None, Some(mv)) if mv.cells().contains(&(x, y)) => ghost(mv.kind),
+                            (cell, _) => paint(cell),
+                        },
+                    )
+                    .collect();
+
+                format!("|{row}|")
+            }
+        };
+
+        // The panels hold escape codes, so they are padded by hand rather than by width
+        let pad = match left.is_empty() {
+            true => " ".repeat(8),
+            false => String::new(),
+        };
+
+        out += &format!("{left}{pad}  {middle}  {right}\n");
+    }
+
+    out
+}
+
+// Redraw from the top left of a cleared screen
+pub fn draw(frame: &Frame) {
+    print!("\x1b[2J\x1b[H{}", render(frame));
+}
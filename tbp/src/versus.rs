@@ -0,0 +1,58 @@
+use bot::bot::BotConfigs;
+use sim::versus::{VersusConfigs, versus as play};
+
+use crate::{args, replay};
+
+pub fn versus(args: &[String]) {
+    let games: u64 = args::parse(args, "--games", 10);
+    let seed: u64 = args::parse(args, "--seed", 0);
+
+    let configs = BotConfigs {
+        width: args::parse(args, "--width", 250),
+        depth: args::parse(args, "--depth", 12),
+        branch: 0,
+        threads: args::parse(args, "--threads", 1),
+    };
+    let bots = [
+        (args::weights(args, "--weights", "--preset"), configs),
+        (
+            args::weights(args, "--against", "--against-preset"),
+            configs,
+        ),
+    ];
+    let defaults = VersusConfigs::default();
+    let versus_configs = VersusConfigs {
+        delay: args::parse(args, "--delay", defaults.delay),
+        messiness: args::parse(args, "--messiness", defaults.messiness),
+        max_pieces: args::parse(args, "--pieces", defaults.max_pieces),
+        ..defaults
+    };
+
+    let mut wins = [0; 2];
+
+    for game in 0..games {
+        let result = play(bots, seed + game, &versus_configs);
+
+        for (i, replay) in result.replays.iter().enumerate() {
+            replay::save(args, &format!("versus-{}-{i}", seed + game), replay);
+        }
+
+        if let Some(winner) = result.winner {
+            wins[winner] += 1;
+        }
+
+        println!(
+            "game {}: winner {} - pieces {}/{} - app {:.3}/{:.3} - received {}/{}",
+            seed + game,
+            result.winner.map_or("-".to_string(), |w| w.to_string()),
+            result.stats[0].pieces,
+            result.stats[1].pieces,
+            result.stats[0].app(),
+            result.stats[1].app(),
+            result.stats[0].received,
+            result.stats[1].received,
+        );
+    }
+
+    println!("wins: {} - {}", wins[0], wins[1]);
+}
@@ -1,8 +1,8 @@
-use std::u64;
+use serde::{Deserialize, Serialize};
 
 use crate::moves::Move;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Board {
     pub cols: [u64; 10],
 }
@@ -21,13 +21,7 @@ impl Board {
     }
 
     pub fn heights(&self) -> [u32; 10] {
-        let mut heights = [0; 10];
-
-        for x in 0..10 {
-            heights[x] = self.height(x);
-        }
-
-        heights
+        std::array::from_fn(|x| self.height(x))
     }
 
     pub fn is_empty(&self) -> bool {
@@ -55,6 +49,26 @@ impl Board {
         }
     }
 
+    // Push the stack up by some garbage lines that all share the same hole
+    pub fn add_garbage(&mut self, lines: u8, hole: usize) {
+        // A whole column's worth or more pushes everything off the top
+        let lines = (lines as u32).min(64);
+        let mask = u64::MAX.checked_shr(64 - lines).unwrap_or(0);
+
+        for (x, col) in self.cols.iter_mut().enumerate() {
+            *col = col.checked_shl(lines).unwrap_or(0);
+
+            if x != hole {
+                *col |= mask;
+            }
+        }
+    }
+
+    // Rows filled all the way across, as a mask
+    pub fn full(&self) -> u64 {
+        self.cols.iter().fold(u64::MAX, |a, c| a & c)
+    }
+
     #[cfg(all(target_arch = "x86_64", target_feature = "bmi2"))]
     pub fn clear_lines(&mut self) -> u8 {
         let mask = self.cols.iter().fold(u64::MAX, |a, c| a & c);
@@ -105,6 +119,12 @@ impl Board {
     }
 }
 
+impl Default for Board {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl std::fmt::Display for Board {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         for y in (0..20).rev() {
@@ -112,9 +132,34 @@ impl std::fmt::Display for Board {
                 write!(f, "{}", if self.has(x, y) { '#' } else { '.' })?;
             }
 
-            write!(f, "\n")?;
+            writeln!(f)?;
         }
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn add_garbage() {
+        let mut board = Board::new();
+
+        board.add_garbage(0, 0);
+        assert!(board.is_empty());
+
+        board.add_garbage(2, 3);
+        assert_eq!(board.count(), 18);
+        assert_eq!(board.cols[3], 0);
+
+        board.add_garbage(64, 5);
+        assert_eq!(board.cols[0], u64::MAX);
+        assert_eq!(board.cols[5], 0);
+
+        board.add_garbage(255, 0);
+        assert_eq!(board.cols[0], 0);
+        assert_eq!(board.cols[1], u64::MAX);
+    }
+}
@@ -0,0 +1,231 @@
+use crate::{board::Board, moves::Move, piece::Piece};
+
+// Rows the colors are kept for, as high as `Board::has` looks
+const ROWS: usize = 40;
+
+// What filled a cell, the bitboard only knows that something did
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Cell {
+    Piece(Piece),
+    Garbage,
+}
+
+// A board that also remembers what filled each cell, every change goes through both so the cells
+// are filled exactly where the bitboard is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ColoredBoard {
+    board: Board,
+    cells: [[Option<Cell>; 10]; ROWS],
+}
+
+impl ColoredBoard {
+    pub const fn new() -> Self {
+        Self {
+            board: Board::new(),
+            cells: [[None; 10]; ROWS],
+        }
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Option<Cell> {
+        self.cells.get(y).and_then(|row| row[x])
+    }
+
+    pub fn place(&mut self, mv: &Move) {
+        self.board.place(mv);
+
+        for (x, y) in mv.cells() {
+            if let Some(row) = self.cells.get_mut(y as usize) {
+                row[x as usize] = Some(Cell::Piece(mv.kind));
+            }
+        }
+    }
+
+    pub fn clear_lines(&mut self) -> u8 {
+        let full = self.board.cols.iter().fold(u64::MAX, |a, c| a & c);
+        let mut kept = 0;
+
+        for y in 0..ROWS {
+            if full >> y & 1 == 0 {
+                self.cells[kept] = self.cells[y];
+                kept += 1;
+            }
+        }
+
+        self.cells[kept..].fill([None; 10]);
+        self.board.clear_lines()
+    }
+
+    pub fn add_garbage(&mut self, lines: u8, hole: usize) {
+        let lines = (lines as usize).min(ROWS);
+
+        self.board.add_garbage(lines as u8, hole);
+        self.cells.copy_within(0..ROWS - lines, lines);
+        self.cells[..lines].fill(std::array::from_fn(|x| {
+            (x != hole).then_some(Cell::Garbage)
+        }));
+    }
+
+    // Catch up with a board changed from outside, garbage pushed under the stack is found by how
+    // far the known cells moved up, any other cell that doesn't match is taken as garbage
+    pub fn sync(&mut self, board: &Board) {
+        let shift = (0..ROWS)
+            .find(|&n| (0..10).all(|x| board.cols[x] >> n == self.board.cols[x]))
+            .unwrap_or(0);
+
+        self.cells.copy_within(0..ROWS - shift, shift);
+        self.cells[..shift].fill([None; 10]);
+
+        for (y, row) in self.cells.iter_mut().enumerate() {
+            for (x, cell) in row.iter_mut().enumerate() {
+                match board.has(x as i8, y as i8) {
+                    true if cell.is_none() => *cell = Some(Cell::Garbage),
+                    false => *cell = None,
+                    true => {}
+                }
+            }
+        }
+
+        self.board = *board;
+    }
+}
+
+impl Default for ColoredBoard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Nothing is known about the cells of a plain board, so they all count as garbage
+impl From<Board> for ColoredBoard {
+    fn from(board: Board) -> Self {
+        let mut colored = Self::new();
+
+        colored.sync(&board);
+        colored
+    }
+}
+
+impl From<ColoredBoard> for Board {
+    fn from(colored: ColoredBoard) -> Self {
+        colored.board
+    }
+}
+
+impl std::fmt::Display for ColoredBoard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for y in (0..20).rev() {
+            for x in 0..10 {
+                let c = match self.get(x, y) {
+                    Some(Cell::Piece(piece)) => format!("{piece:?}"),
+                    Some(Cell::Garbage) => "G".to_string(),
+                    None => ".".to_string(),
+                };
+
+                write!(f, "{c}")?;
+            }
+
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use strum::IntoEnumIterator;
+
+    use super::*;
+    use crate::{movegen::movegen, piece::Rotation};
+
+    fn mirrored(colored: &ColoredBoard) -> bool {
+        (0..ROWS).all(|y| {
+            (0..10).all(|x| colored.get(x, y).is_some() == colored.board.has(x as i8, y as i8))
+        })
+    }
+
+    #[test]
+    fn mirror() {
+        let mut colored = ColoredBoard::new();
+        let mut board = Board::new();
+        let mut cleared = 0;
+
+        // Placements reaching the least high for every piece in turn with some garbage in between
+        for (i, kind) in Piece::iter().cycle().take(40).enumerate() {
+            let mv = movegen(&board, kind)
+                .into_iter()
+                .min_by_key(|mv| {
+                    let top = mv.cells().iter().map(|&(_, y)| y).max();
+
+                    (top, (mv.x as usize + i) % 10)
+                })
+                .unwrap();
+
+            colored.place(&mv);
+            board.place(&mv);
+
+            let lines = board.clear_lines();
+
+            assert_eq!(colored.clear_lines(), lines);
+
+            cleared += lines;
+
+            if i % 7 == 3 {
+                colored.add_garbage(1, i % 10);
+                board.add_garbage(1, i % 10);
+            }
+
+            assert_eq!(colored.board, board);
+            assert!(mirrored(&colored));
+        }
+
+        assert!(cleared > 0);
+    }
+
+    #[test]
+    fn sync() {
+        let mut colored = ColoredBoard::new();
+
+        colored.place(&Move {
+            x: 1,
+            y: 0,
+            r: Rotation::North,
+            kind: Piece::L,
+            tspin: None,
+        });
+
+        // Garbage pushed under the stack lifts the piece with its color
+        let mut board = colored.board;
+
+        board.add_garbage(2, 3);
+        colored.sync(&board);
+
+        assert_eq!(colored.board, board);
+        assert!(mirrored(&colored));
+        assert_eq!(colored.get(0, 2), Some(Cell::Piece(Piece::L)));
+        assert_eq!(colored.get(2, 3), Some(Cell::Piece(Piece::L)));
+        assert_eq!(colored.get(0, 0), Some(Cell::Garbage));
+        assert_eq!(colored.get(3, 1), None);
+
+        // Clearing both garbage lines brings the piece back down
+        colored.place(&Move {
+            x: 3,
+            y: 2,
+            r: Rotation::East,
+            kind: Piece::I,
+            tspin: None,
+        });
+
+        assert_eq!(colored.clear_lines(), 2);
+        assert_eq!(colored.get(0, 0), Some(Cell::Piece(Piece::L)));
+        assert_eq!(colored.get(3, 0), Some(Cell::Piece(Piece::I)));
+        assert_eq!(
+            ColoredBoard::from(*colored.board()).get(0, 0),
+            Some(Cell::Garbage)
+        );
+    }
+}
@@ -0,0 +1,161 @@
+use std::{collections::VecDeque, sync::LazyLock};
+
+use strum::IntoEnumIterator;
+
+use crate::{
+    board::Board,
+    moves::{Move, MoveMap},
+    piece::{Piece, Rotation},
+};
+
+// Fewest presses for every piece, rotation and column on an empty board, hard drop included
+static TABLE: LazyLock<[[[u8; 10]; 4]; 7]> = LazyLock::new(|| {
+    let mut table = [[[0; 10]; 4]; 7];
+
+    for kind in Piece::iter() {
+        search(&Board::new(), kind, |drop, presses| {
+            let entry = &mut table[kind as usize][drop.r as usize][drop.x as usize];
+
+            if *entry == 0 {
+                *entry = presses as u8;
+            }
+
+            false
+        });
+    }
+
+    table
+});
+
+// Walk every position the piece can reach from spawn by fewest presses first, a press is a shift,
+// a das to the wall, a rotation or a soft drop to the floor, and hand out where a hard drop from
+// there locks (canonicalized) along with the presses it took, until `visit` is done
+fn search(board: &Board, kind: Piece, mut visit: impl FnMut(&Move, u32) -> bool) {
+    let collisions = MoveMap::collsions(board, kind);
+    let spawn = Move {
+        x: 4,
+        y: 20,
+        r: Rotation::North,
+        kind,
+        tspin: None,
+    };
+
+    if collisions.has(&spawn) {
+        return;
+    }
+
+    let mut visited = MoveMap::new();
+    let mut queue = VecDeque::from([(spawn, 0)]);
+
+    visited.set(&spawn);
+
+    while let Some((mv, presses)) = queue.pop_front() {
+        let drop = mv.dropped(&collisions);
+
+        if visit(&drop.canonicalized(), presses + 1) {
+            return;
+        }
+
+        let mut das = [mv.shifted(&collisions, -1), mv.shifted(&collisions, 1)];
+
+        for (das, dx) in das.iter_mut().zip([-1, 1]) {
+            while let Some(next) = das.and_then(|das| das.shifted(&collisions, dx)) {
+                *das = Some(next);
+            }
+        }
+
+        let next = [
+            mv.shifted(&collisions, -1),
+            mv.shifted(&collisions, 1),
+            das[0],
+            das[1],
+            mv.rotated(&collisions, board, mv.r.cw()),
+            mv.rotated(&collisions, board, mv.r.ccw()),
+            Some(drop),
+        ];
+
+        for next in next.into_iter().flatten() {
+            if !visited.has(&next) {
+                visited.set(&next);
+                queue.push_back((next, presses + 1));
+            }
+        }
+    }
+}
+
+// Fewest presses to lock the piece where the move ends on this board, none if it can't get there
+pub fn inputs(board: &Board, mv: &Move) -> Option<u32> {
+    let target = mv.canonicalized();
+    let mut inputs = None;
+
+    search(board, mv.kind, |drop, presses| {
+        if (drop.x, drop.y, drop.r) == (target.x, target.y, target.r) {
+            inputs = Some(presses);
+        }
+
+        inputs.is_some()
+    });
+
+    inputs
+}
+
+// Presses the move's column and rotation take on an open board, what a clean placement costs
+pub fn finesse(mv: &Move) -> u32 {
+    let mv = mv.canonicalized();
+
+    TABLE[mv.kind as usize][mv.r as usize][mv.x as usize] as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mv(kind: Piece, x: i8, y: i8, r: Rotation) -> Move {
+        Move {
+            x,
+            y,
+            r,
+            kind,
+            tspin: None,
+        }
+    }
+
+    #[test]
+    fn finesse() {
+        // Straight down, one shift, a das to the wall
+        assert_eq!(super::finesse(&mv(Piece::T, 4, 0, Rotation::North)), 1);
+        assert_eq!(super::finesse(&mv(Piece::T, 5, 0, Rotation::North)), 2);
+        assert_eq!(super::finesse(&mv(Piece::T, 1, 0, Rotation::North)), 2);
+        assert_eq!(super::finesse(&mv(Piece::I, 1, 0, Rotation::North)), 2);
+
+        // A rotation and a das, either way round for the vertical I
+        assert_eq!(super::finesse(&mv(Piece::I, 0, 1, Rotation::West)), 3);
+        assert_eq!(super::finesse(&mv(Piece::I, 0, 2, Rotation::East)), 3);
+    }
+
+    #[test]
+    fn inputs() {
+        let mut board = Board::new();
+
+        assert_eq!(
+            super::inputs(&board, &mv(Piece::T, 4, 0, Rotation::North)),
+            Some(1)
+        );
+
+        // An overhang over columns 0 to 2, the T has to soft drop and slide under it
+        board.cols[0] = 0b100;
+        board.cols[1] = 0b100;
+        board.cols[2] = 0b100;
+
+        let tuck = mv(Piece::T, 1, 0, Rotation::North);
+
+        assert!(super::inputs(&board, &tuck).unwrap() > super::finesse(&tuck));
+
+        // Under the whole row there is no way in
+        for col in &mut board.cols {
+            *col = 0b100;
+        }
+
+        assert_eq!(super::inputs(&board, &tuck), None);
+    }
+}
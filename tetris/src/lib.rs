@@ -2,6 +2,8 @@
 
 pub mod bag;
 pub mod board;
+pub mod colored;
+pub mod finesse;
 pub mod movegen;
 pub mod moves;
 pub mod piece;
@@ -76,36 +76,36 @@ fn expand(
         );
     }
 
-    if let Some(right) = mv.shifted(collisions, 1) {
-        if !visited.has(&right) {
-            visited.set(&right);
-
-            expand(
-                &right,
-                collisions,
-                board,
-                visited,
-                locked,
-                tspin_locked,
-                list,
-            );
-        }
+    if let Some(right) = mv.shifted(collisions, 1)
+        && !visited.has(&right)
+    {
+        visited.set(&right);
+
+        expand(
+            &right,
+            collisions,
+            board,
+            visited,
+            locked,
+            tspin_locked,
+            list,
+        );
     }
 
-    if let Some(left) = mv.shifted(collisions, -1) {
-        if !visited.has(&left) {
-            visited.set(&left);
-
-            expand(
-                &left,
-                collisions,
-                board,
-                visited,
-                locked,
-                tspin_locked,
-                list,
-            );
-        }
+    if let Some(left) = mv.shifted(collisions, -1)
+        && !visited.has(&left)
+    {
+        visited.set(&left);
+
+        expand(
+            &left,
+            collisions,
+            board,
+            visited,
+            locked,
+            tspin_locked,
+            list,
+        );
     }
 
     if mv.kind == Piece::O {
@@ -156,10 +156,10 @@ pub fn movegen(board: &Board, kind: Piece) -> Vec<Move> {
                 }
 
                 list.push(Move {
-                    x: x,
+                    x,
                     y: collisions.data[r as usize].height(x as usize) as i8,
-                    r: r,
-                    kind: kind,
+                    r,
+                    kind,
                     tspin: None,
                 });
             }
@@ -186,10 +186,10 @@ pub fn movegen(board: &Board, kind: Piece) -> Vec<Move> {
                 }
 
                 let dropped = Move {
-                    x: x,
+                    x,
                     y: collisions.data[r as usize].height(x as usize) as i8,
-                    r: r,
-                    kind: kind,
+                    r,
+                    kind,
                     tspin: None,
                 };
 
@@ -216,7 +216,7 @@ pub fn movegen(board: &Board, kind: Piece) -> Vec<Move> {
         x: 4,
         y: 20,
         r: Rotation::North,
-        kind: kind,
+        kind,
         tspin: None,
     };
 
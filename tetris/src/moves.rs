@@ -1,5 +1,4 @@
-use std::u64;
-
+use serde::{Deserialize, Serialize};
 use strum::IntoEnumIterator;
 
 use crate::{
@@ -7,13 +6,13 @@ use crate::{
     piece::{Piece, Rotation},
 };
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Tspin {
     Mini,
     Full,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Move {
     pub x: i8,
     pub y: i8,
@@ -64,9 +63,13 @@ impl Move {
             _ => &JLSTZ_SRS_TABLE,
         };
 
-        for i in 0..5 {
-            let dx = offsets[self.r as usize][i].0 - offsets[target as usize][i].0;
-            let dy = offsets[self.r as usize][i].1 - offsets[target as usize][i].1;
+        for (i, (from, to)) in offsets[self.r as usize]
+            .iter()
+            .zip(offsets[target as usize].iter())
+            .enumerate()
+        {
+            let dx = from.0 - to.0;
+            let dy = from.1 - to.1;
 
             let mut rotated = Self {
                 x: self.x + dx,
@@ -249,6 +252,12 @@ impl MoveMap {
     }
 }
 
+impl Default for MoveMap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl std::fmt::Display for Tspin {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -1,7 +1,8 @@
 use enumset::*;
+use serde::{Deserialize, Serialize};
 use strum::*;
 
-#[derive(Debug, PartialOrd, Ord, Hash, EnumIter, FromRepr, EnumSetType)]
+#[derive(Debug, PartialOrd, Ord, Hash, EnumIter, FromRepr, EnumSetType, Serialize, Deserialize)]
 #[enumset(repr = "u8")]
 pub enum Piece {
     I,
@@ -13,7 +14,20 @@ pub enum Piece {
     Z,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, EnumIter, FromRepr)]
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    EnumIter,
+    FromRepr,
+    Serialize,
+    Deserialize,
+)]
 pub enum Rotation {
     North,
     East,
@@ -1,3 +1,5 @@
+use serde::{Deserialize, Serialize};
+
 use crate::{
     bag::{Bag, update_bag},
     board::Board,
@@ -11,14 +13,14 @@ pub fn combo_bonus(index: usize) -> u8 {
     TABLE[index.min(TABLE.len() - 1)]
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Lock {
     pub cleared: u8,
     pub sent: u8,
     pub softdrop: bool,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct State {
     pub board: Board,
     pub hold: Option<Piece>,
@@ -28,6 +30,12 @@ pub struct State {
     pub combo: u8,
 }
 
+impl Default for State {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl State {
     pub const fn new() -> Self {
         Self {
@@ -0,0 +1,194 @@
+use std::{io::ErrorKind, path::PathBuf};
+
+use bot::{bot::BotConfigs, eval::Weights};
+use sim::{
+    parallel,
+    solo::{SoloConfigs, solo},
+    versus::{VersusConfigs, versus},
+};
+
+use crate::spsa::{Gains, Spsa};
+
+mod spsa;
+mod train;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Solo,
+    Versus,
+}
+
+#[derive(Debug, Clone)]
+struct Options {
+    mode: Mode,
+    iterations: usize,
+    games: usize,
+    pieces: usize,
+    width: usize,
+    threads: usize,
+    seed: u64,
+    checkpoint: PathBuf,
+}
+
+impl Options {
+    fn parse(args: &[String]) -> Result<Self, String> {
+        let mut options = Self {
+            mode: Mode::Solo,
+            iterations: 1000,
+            games: 16,
+            pieces: 300,
+            width: 100,
+            threads: parallel::threads(),
+            seed: 0,
+            checkpoint: PathBuf::from("tuner.json"),
+        };
+
+        for pair in args.chunks(2) {
+            let [key, value] = pair else {
+                return Err(format!("missing value for {}", pair[0]));
+            };
+
+            let number = || {
+                value
+                    .parse()
+                    .map_err(|_| format!("invalid value for {key}"))
+            };
+
+            match &key[..] {
+                "--mode" => {
+                    options.mode = match &value[..] {
+                        "solo" => Mode::Solo,
+                        "versus" => Mode::Versus,
+                        _ => return Err(format!("unknown mode {value}")),
+                    }
+                }
+                "--iterations" => options.iterations = number()?,
+                "--games" => options.games = number()?,
+                "--pieces" => options.pieces = number()?,
+                "--width" => options.width = number()?,
+                "--threads" => options.threads = number()?,
+                "--seed" => options.seed = number()? as u64,
+                "--checkpoint" => options.checkpoint = PathBuf::from(value),
+                _ => return Err(format!("unknown option {key}")),
+            }
+        }
+
+        Ok(options)
+    }
+
+    fn configs(&self) -> BotConfigs {
+        BotConfigs {
+            width: self.width,
+            depth: 6,
+            branch: 0,
+            threads: 1,
+        }
+    }
+}
+
+// Mean attack per piece of plus minus the one of minus, dying costs a whole point
+fn score_solo(options: &Options, seed: u64, plus: Weights, minus: Weights) -> f64 {
+    let solo_configs = SoloConfigs {
+        max_pieces: options.pieces,
+        ..SoloConfigs::default()
+    };
+
+    let scores = parallel::map(options.games * 2, options.threads, |i| {
+        let weights = if i < options.games { plus } else { minus };
+        let result = solo(
+            weights,
+            options.configs(),
+            seed + (i % options.games) as u64,
+            &solo_configs,
+        );
+
+        result.stats.app() - result.dead as u8 as f64
+    });
+
+    let (plus, minus) = scores.split_at(options.games);
+
+    (plus.iter().sum::<f64>() - minus.iter().sum::<f64>()) / options.games as f64
+}
+
+// Plus against minus on shared seeds with sides swapped, +1 for a plus win and -1 for a loss
+fn score_versus(options: &Options, seed: u64, plus: Weights, minus: Weights) -> f64 {
+    let versus_configs = VersusConfigs {
+        max_pieces: options.pieces,
+        ..VersusConfigs::default()
+    };
+
+    let outcomes = parallel::map(options.games * 2, options.threads, |i| {
+        let swapped = i % 2 == 1;
+        let bots = match swapped {
+            false => [(plus, options.configs()), (minus, options.configs())],
+            true => [(minus, options.configs()), (plus, options.configs())],
+        };
+
+        match versus(bots, seed + (i / 2) as u64, &versus_configs).winner {
+            Some(winner) if (winner == 0) != swapped => 1.0,
+            Some(_) => -1.0,
+            None => 0.0,
+        }
+    });
+
+    outcomes.iter().sum::<f64>() / outcomes.len() as f64
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+
+    // Fit a learned model to self-play instead of tuning the weights
+    if args.get(1).map(String::as_str) == Some("train") {
+        if let Err(error) = train::train(&args[2..]) {
+            eprintln!("{error}");
+            std::process::exit(1);
+        }
+
+        return;
+    }
+
+    let options = match Options::parse(&args[1..]) {
+        Ok(options) => options,
+        Err(error) => {
+            eprintln!("{error}");
+            std::process::exit(1);
+        }
+    };
+
+    let gains = Gains::default();
+
+    let mut spsa = match Spsa::load(&options.checkpoint) {
+        Ok(spsa) => {
+            println!("resuming from iteration {}", spsa.iteration);
+            spsa
+        }
+        Err(error) if error.kind() == ErrorKind::NotFound => {
+            Spsa::new(&Weights::default(), options.seed)
+        }
+        // Starting over would overwrite it on the first save
+        Err(error) => {
+            eprintln!("{}: {error}", options.checkpoint.display());
+            std::process::exit(1);
+        }
+    };
+
+    while spsa.iteration < options.iterations {
+        let (delta, plus, minus) = spsa.perturb(&gains);
+        let seed = spsa.seed + (spsa.iteration * options.games) as u64;
+
+        let diff = match options.mode {
+            Mode::Solo => score_solo(&options, seed, plus, minus),
+            Mode::Versus => score_versus(&options, seed, plus, minus),
+        };
+
+        spsa.update(&gains, &delta, diff);
+
+        if let Err(error) = spsa.save(&options.checkpoint) {
+            eprintln!("failed to save checkpoint: {error}");
+        }
+
+        println!("iteration {}: diff {:+.4}", spsa.iteration, diff);
+    }
+
+    println!("{}", spsa.current().to_json());
+}
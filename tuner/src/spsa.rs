@@ -0,0 +1,176 @@
+use std::{fs, io, path::Path};
+
+use rand::{Rng, SeedableRng, rngs::StdRng};
+use serde::{Deserialize, Serialize};
+
+use bot::eval::Weights;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Gains {
+    pub a: f64,
+    pub c: f64,
+    pub stability: f64,
+    pub alpha: f64,
+    pub gamma: f64,
+}
+
+// Optimizer state, also the checkpoint format
+// Parameters are tuned relative to their scale so one step size fits every weight, `params` is how
+// many weights there were when the checkpoint was started so a stale one is caught on load
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Spsa {
+    pub iteration: usize,
+    pub seed: u64,
+    pub params: usize,
+    pub theta: Vec<f64>,
+    pub scale: Vec<f64>,
+}
+
+impl Default for Gains {
+    fn default() -> Self {
+        Self {
+            a: 0.5,
+            c: 0.1,
+            stability: 10.0,
+            alpha: 0.602,
+            gamma: 0.101,
+        }
+    }
+}
+
+impl Spsa {
+    pub fn new(weights: &Weights, seed: u64) -> Self {
+        let params = weights.params();
+        let scale: Vec<f64> = params.iter().map(|&p| (p.abs() as f64).max(50.0)).collect();
+        let theta = params
+            .iter()
+            .zip(&scale)
+            .map(|(&p, s)| p as f64 / s)
+            .collect();
+
+        Self {
+            iteration: 0,
+            seed,
+            params: params.len(),
+            theta,
+            scale,
+        }
+    }
+
+    pub fn weights(&self, theta: &[f64]) -> Weights {
+        let mut weights = Weights::default();
+
+        for ((param, t), s) in weights.params_mut().into_iter().zip(theta).zip(&self.scale) {
+            *param = (t * s).round() as i32;
+        }
+
+        weights
+    }
+
+    pub fn current(&self) -> Weights {
+        self.weights(&self.theta)
+    }
+
+    // Draw this iteration's perturbation, returning it with the plus and minus weights
+    pub fn perturb(&self, gains: &Gains) -> (Vec<f64>, Weights, Weights) {
+        let mut rng = StdRng::seed_from_u64(self.seed.wrapping_add(self.iteration as u64));
+        let ck = gains.c / (self.iteration as f64 + 1.0).powf(gains.gamma);
+
+        let delta: Vec<f64> = self
+            .theta
+            .iter()
+            .map(|_| if rng.random_bool(0.5) { ck } else { -ck })
+            .collect();
+        let plus: Vec<f64> = self.theta.iter().zip(&delta).map(|(t, d)| t + d).collect();
+        let minus: Vec<f64> = self.theta.iter().zip(&delta).map(|(t, d)| t - d).collect();
+
+        (delta, self.weights(&plus), self.weights(&minus))
+    }
+
+    // Step along the gradient estimated from the score difference between plus and minus
+    pub fn update(&mut self, gains: &Gains, delta: &[f64], diff: f64) {
+        let ak = gains.a / (self.iteration as f64 + 1.0 + gains.stability).powf(gains.alpha);
+
+        for (t, d) in self.theta.iter_mut().zip(delta) {
+            *t += ak * diff / (2.0 * d);
+        }
+
+        self.iteration += 1;
+    }
+
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let json = fs::read_to_string(path)?;
+        let spsa: Self = serde_json::from_str(&json)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+        let params = Weights::default().params().len();
+
+        if spsa.params != params || spsa.theta.len() != params || spsa.scale.len() != params {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "checkpoint has {} parameters but the weights have {params}",
+                    spsa.theta.len()
+                ),
+            ));
+        }
+
+        Ok(spsa)
+    }
+
+    // Write to a temporary file first so a killed run never leaves a broken checkpoint
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let json = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+        let temp = path.with_extension("tmp");
+
+        fs::write(&temp, json)?;
+        fs::rename(&temp, path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checkpoint() {
+        let dir = std::env::temp_dir().join(format!("spsa-{}", std::process::id()));
+        let path = dir.join("tuner.json");
+
+        fs::create_dir_all(&dir).unwrap();
+
+        let spsa = Spsa::new(&Weights::default(), 7);
+
+        spsa.save(&path).unwrap();
+
+        let loaded = Spsa::load(&path).unwrap();
+
+        assert_eq!(loaded.theta, spsa.theta);
+        assert_eq!(loaded.current(), Weights::default());
+
+        // A checkpoint from before a weight was added doesn't line up with the weights anymore
+        let mut stale = spsa.clone();
+
+        stale.params -= 1;
+        stale.theta.pop();
+        stale.scale.pop();
+        stale.save(&path).unwrap();
+
+        assert_eq!(
+            Spsa::load(&path).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+
+        fs::write(&path, "{}").unwrap();
+
+        assert_eq!(
+            Spsa::load(&path).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+        assert_eq!(
+            Spsa::load(&dir.join("missing.json")).unwrap_err().kind(),
+            io::ErrorKind::NotFound
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,289 @@
+use std::path::PathBuf;
+
+use rand::{Rng, SeedableRng, rngs::StdRng, seq::SliceRandom};
+
+use bot::{
+    bot::BotConfigs,
+    eval::Weights,
+    features::{FEATURES, features},
+    model::{Layer, Model},
+};
+use sim::{parallel, player::Player};
+
+// Lines of attack a top out costs, so the model learns to fear dying more than it likes sending
+const DEATH: f32 = 20.0;
+
+// Keeps single bad samples from blowing up the weights
+const CLIP: f32 = 10.0;
+
+#[derive(Debug, Clone)]
+struct Options {
+    games: usize,
+    pieces: usize,
+    horizon: usize,
+    hidden: usize,
+    epochs: usize,
+    rate: f32,
+    width: usize,
+    threads: usize,
+    seed: u64,
+    out: PathBuf,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Sample {
+    pub input: [f32; FEATURES],
+    pub target: f32,
+}
+
+impl Options {
+    fn parse(args: &[String]) -> Result<Self, String> {
+        let mut options = Self {
+            games: 32,
+            pieces: 300,
+            horizon: 20,
+            hidden: 0,
+            epochs: 20,
+            rate: 0.001,
+            width: 100,
+            threads: parallel::threads(),
+            seed: 0,
+            out: PathBuf::from("model.json"),
+        };
+
+        for pair in args.chunks(2) {
+            let [key, value] = pair else {
+                return Err(format!("missing value for {}", pair[0]));
+            };
+
+            let invalid = || format!("invalid value for {key}");
+
+            match &key[..] {
+                "--games" => options.games = value.parse().map_err(|_| invalid())?,
+                "--pieces" => options.pieces = value.parse().map_err(|_| invalid())?,
+                "--horizon" => options.horizon = value.parse().map_err(|_| invalid())?,
+                "--hidden" => options.hidden = value.parse().map_err(|_| invalid())?,
+                "--epochs" => options.epochs = value.parse().map_err(|_| invalid())?,
+                "--rate" => options.rate = value.parse().map_err(|_| invalid())?,
+                "--width" => options.width = value.parse().map_err(|_| invalid())?,
+                "--threads" => options.threads = value.parse().map_err(|_| invalid())?,
+                "--seed" => options.seed = value.parse().map_err(|_| invalid())?,
+                "--out" => options.out = PathBuf::from(value),
+                _ => return Err(format!("unknown option {key}")),
+            }
+        }
+
+        Ok(options)
+    }
+}
+
+// Self-play one game with the handcrafted weights, labelling every position with the attack sent
+// over the next `horizon` pieces, or the death penalty when the game ends before that
+pub fn record(configs: BotConfigs, seed: u64, pieces: usize, horizon: usize) -> Vec<Sample> {
+    let mut player = Player::new(Weights::default(), configs, seed, 5);
+    let mut inputs = Vec::new();
+    let mut sent = Vec::new();
+    let mut dead = false;
+
+    while player.stats.pieces < pieces {
+        let Ok((_, lock)) = player.play() else {
+            dead = true;
+            break;
+        };
+
+        inputs.push(features(player.bot.root(), 0));
+        sent.push(lock.sent as f32);
+    }
+
+    inputs
+        .into_iter()
+        .enumerate()
+        .filter_map(|(i, input)| {
+            let end = i + 1 + horizon;
+            let future: f32 = sent[i + 1..end.min(sent.len())].iter().sum();
+
+            match (end > sent.len(), dead) {
+                (false, _) => Some(future),
+                (true, true) => Some(future - DEATH),
+                (true, false) => None,
+            }
+            .map(|target| Sample { input, target })
+        })
+        .collect()
+}
+
+// Relu layers of `hidden` units ahead of the output, none gives a linear model
+pub fn init(hidden: usize, rng: &mut impl Rng) -> Model {
+    if hidden == 0 {
+        return Model::linear();
+    }
+
+    let mut first = Layer::zeros(FEATURES, hidden);
+    let bound = (6.0 / FEATURES as f32).sqrt();
+
+    for weight in first.weights.iter_mut().flatten() {
+        *weight = rng.random_range(-bound..bound);
+    }
+
+    let mut output = Layer::zeros(hidden, 1);
+    let bound = (6.0 / hidden as f32).sqrt();
+
+    for weight in output.weights.iter_mut().flatten() {
+        *weight = rng.random_range(-bound..bound);
+    }
+
+    Model::new(vec![first, output]).expect("shapes line up")
+}
+
+// One step of plain sgd on the squared error, returning that error
+fn step(model: &mut Model, sample: &Sample, rate: f32) -> f32 {
+    let count = model.layers().len();
+    let mut activations = vec![sample.input.to_vec()];
+
+    for (i, layer) in model.layers().iter().enumerate() {
+        let mut values = layer.forward(activations.last().unwrap());
+
+        if i + 1 < count {
+            values.iter_mut().for_each(|v| *v = v.max(0.0));
+        }
+
+        activations.push(values);
+    }
+
+    let error = activations[count][0] - sample.target;
+    let mut gradient = vec![error.clamp(-CLIP, CLIP)];
+
+    for (i, layer) in model.layers_mut().iter_mut().enumerate().rev() {
+        let input = &activations[i];
+        let mut back = vec![0.0; layer.inputs()];
+
+        for ((row, bias), g) in layer.weights.iter_mut().zip(&mut layer.bias).zip(&gradient) {
+            for ((weight, x), b) in row.iter_mut().zip(input).zip(&mut back) {
+                *b += *weight * g;
+                *weight -= rate * g * x;
+            }
+
+            *bias -= rate * g;
+        }
+
+        // Only units the relu let through pass the gradient back
+        for (b, x) in back.iter_mut().zip(input) {
+            if i > 0 && *x <= 0.0 {
+                *b = 0.0;
+            }
+        }
+
+        gradient = back;
+    }
+
+    error * error
+}
+
+// Fit the model over shuffled epochs, returning the mean squared error of the last one
+pub fn fit(
+    model: &mut Model,
+    samples: &mut [Sample],
+    epochs: usize,
+    rate: f32,
+    rng: &mut impl Rng,
+) -> f32 {
+    let mut loss = 0.0;
+
+    for epoch in 0..epochs {
+        samples.shuffle(rng);
+
+        loss = samples
+            .iter()
+            .map(|sample| step(model, sample, rate))
+            .sum::<f32>()
+            / samples.len().max(1) as f32;
+
+        println!("epoch {}: loss {loss:.4}", epoch + 1);
+    }
+
+    loss
+}
+
+pub fn train(args: &[String]) -> Result<(), String> {
+    let options = Options::parse(args)?;
+    let configs = BotConfigs {
+        width: options.width,
+        depth: 6,
+        branch: 0,
+        threads: 1,
+    };
+
+    let games = parallel::map(options.games, options.threads, |i| {
+        record(
+            configs,
+            options.seed + i as u64,
+            options.pieces,
+            options.horizon,
+        )
+    });
+    let mut samples: Vec<_> = games.into_iter().flatten().collect();
+
+    println!("{} samples from {} games", samples.len(), options.games);
+
+    let mut rng = StdRng::seed_from_u64(options.seed);
+    let mut model = init(options.hidden, &mut rng);
+
+    fit(
+        &mut model,
+        &mut samples,
+        options.epochs,
+        options.rate,
+        &mut rng,
+    );
+
+    std::fs::write(&options.out, model.to_json())
+        .map_err(|error| format!("failed to write {}: {error}", options.out.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fit() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut model = init(4, &mut rng);
+
+        // The target only depends on the first feature
+        let mut samples: Vec<_> = (0..64)
+            .map(|i| {
+                let mut input = [0.0; FEATURES];
+
+                input[0] = (i % 8) as f32 / 8.0;
+
+                Sample {
+                    input,
+                    target: 2.0 * input[0] + 1.0,
+                }
+            })
+            .collect();
+
+        let before = samples
+            .iter()
+            .map(|s| (model.predict(&s.input) - s.target).powi(2))
+            .sum::<f32>();
+        let after = super::fit(&mut model, &mut samples, 200, 0.05, &mut rng) * 64.0;
+
+        assert!(after < before / 10.0);
+    }
+
+    #[test]
+    fn record() {
+        let configs = BotConfigs {
+            width: 16,
+            depth: 2,
+            branch: 0,
+            threads: 1,
+        };
+        let samples = super::record(configs, 0, 30, 10);
+
+        // Without a top out the last `horizon` positions have no label yet
+        assert_eq!(samples.len(), 20);
+        assert!(samples.iter().all(|s| s.target >= 0.0));
+    }
+}